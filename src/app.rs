@@ -1,18 +1,25 @@
 #[allow(unused_imports)]
 use crate::{dbg, debug, error};
 
-use anyhow::Result;
-use crossterm::event::{Event, EventStream};
+use anyhow::{bail, Context, Result};
+use crossterm::event::{
+    Event, EventStream, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 use derivative::Derivative;
 use futures::{FutureExt, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    time::{Duration, Instant},
+};
 use tokio::select;
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
     text::{Span, Spans, Text},
-    widgets::{Block, Borders, List, ListItem, ListState},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
     Frame, Terminal,
 };
 
@@ -20,19 +27,48 @@ use crate::{
     content::traits::Content,
     content::{
         main_provider::MainProvider,
+        todo::{format_clock, format_duration, running_timer, Todo, TodoStatus},
         traits::{DisplayContext, DrawContext, WidgetOutput},
     },
-    key,
+    ctrl, key,
+    overlay::{
+        history::History,
+        palette::{Palette, PaletteAction},
+        profile_picker::{PickerAction, ProfilePicker},
+        prompt::{Prompt, PromptAction},
+        search::{Search, SearchAction},
+        Overlay,
+    },
     register::{ContentRegister, Id},
-    service::{db::DBHandler, editors::EditManager},
+    service::{
+        config::{profile, set_profile},
+        db::{self, DBHandler},
+        editors::EditManager,
+        journal::Journal,
+        lock::{self, Holder},
+        merge::merge_registers,
+        notify::notify,
+        pomodoro::{Phase, Session},
+        remind::Reminders,
+        storage,
+        theme::theme,
+        tree::{self, Found},
+        watch::FileStamp,
+    },
+    shift,
     stack::ContentStack,
-    traits::{Display, Drawable, EventAction, EventHandler, SelectedIndex, Widget},
+    status_bar::{Level, StatusBar},
+    traits::{
+        key_press, CommandInfo, Display, Drawable, EventAction, EventHandler, Mode, SelectedIndex,
+        Widget,
+    },
 };
 
 pub struct AppActionContext<'a> {
     pub register: &'a mut ContentRegister<Content, Id>,
     pub editor: &'a mut EditManager,
     pub stack: &'a mut ContentStack,
+    pub status: &'a mut StatusBar,
 }
 impl<'a> From<&'a mut App> for AppActionContext<'a> {
     fn from(a: &'a mut App) -> Self {
@@ -40,6 +76,7 @@ impl<'a> From<&'a mut App> for AppActionContext<'a> {
             register: &mut a.content_register,
             editor: &mut a.editor,
             stack: &mut a.stack,
+            status: &mut a.status,
         }
     }
 }
@@ -49,6 +86,7 @@ impl<'a, 'b: 'a> From<&'a mut AppActionContext<'b>> for AppActionContext<'a> {
             register: a.register,
             editor: a.editor,
             stack: a.stack,
+            status: a.status,
         }
     }
 }
@@ -69,9 +107,28 @@ pub enum AppAction {
     MoveUp,
     MoveRight,
     MoveLeft,
+    /// shows the text in the status bar for a while
+    Message {
+        text: Cow<'static, str>,
+        level: Level,
+    },
     None,
 }
 impl AppAction {
+    pub fn info<T: Into<Cow<'static, str>>>(text: T) -> Self {
+        Self::Message {
+            text: text.into(),
+            level: Level::Info,
+        }
+    }
+
+    pub fn error<T: Into<Cow<'static, str>>>(text: T) -> Self {
+        Self::Message {
+            text: text.into(),
+            level: Level::Error,
+        }
+    }
+
     pub fn apply<'a>(self, ctx: &mut AppActionContext<'a>) -> Result<()> {
         dbg!(&self);
         match self {
@@ -97,6 +154,14 @@ impl AppAction {
                     .map(|e| e.as_provider().map(|e| e.get_selected()))
                     .flatten()
                     .flatten();
+                // todos with subtasks open their subtask list
+                let id = id.map(|id| {
+                    ctx.register
+                        .get(id)
+                        .and_then(|e| e.as_any().downcast_ref::<Todo>())
+                        .and_then(|t| t.subtasks)
+                        .unwrap_or(id)
+                });
                 if id
                     .map(|id| ctx.register.get(id))
                     .flatten()
@@ -109,6 +174,7 @@ impl AppAction {
             Self::MoveLeft => {
                 let _ = ctx.stack.pop();
             }
+            Self::Message { text, level } => ctx.status.post(text, level),
             Self::None => (),
         }
         Ok(())
@@ -140,7 +206,24 @@ pub struct App {
     pub stack: ContentStack,
     pub content_register: ContentRegister<Content, Id>,
     pub editor: EditManager,
+    pub overlay: Option<Overlay>,
     pub quit: bool,
+    /// the db file as of the last load or save
+    db_stamp: Option<FileStamp>,
+    /// the register as of the last load or save, the base when merging in outside changes
+    db_base: serde_yaml::Value,
+    /// outside change the user chose to keep editing over
+    ignored_stamp: Option<FileStamp>,
+    /// another instance holds the db, so nothing is saved
+    read_only: Option<Holder>,
+    journal: Journal,
+    /// items changed since the last save. None if that is not known (eg after a load), the whole db is saved then
+    unsaved: Option<HashSet<Id>>,
+    pomodoro: Option<Session>,
+    reminders: Reminders,
+    status: StatusBar,
+    /// when and where the left button was last pressed, to tell double clicks
+    last_click: Option<(Instant, u16, u16)>,
 }
 
 impl App {
@@ -153,48 +236,366 @@ impl App {
             stack,
             content_register,
             editor: Default::default(),
+            overlay: None,
             quit: false,
+            db_stamp: None,
+            db_base: serde_yaml::Value::Null,
+            ignored_stamp: None,
+            read_only: None,
+            journal: Journal::new(&profile().db_path),
+            unsaved: None,
+            pomodoro: None,
+            reminders: Default::default(),
+            status: Default::default(),
+            last_click: None,
         };
         a
     }
 
     pub fn load() -> Result<Self> {
-        let a = DBHandler::load()?
-            .map(|db| Self {
+        let mut a = match DBHandler::load()? {
+            Some(db) => Self {
+                stack: ContentStack::new(db.main_provider().context("no main provider in db")?),
                 content_register: db.register,
                 editor: db.editor,
                 ..Self::new()
-            })
-            .unwrap_or(Self::new());
+            },
+            None => Self::new(),
+        };
+        a.synced()?;
+        a.read_only = lock::holder(&profile().db_path);
+        if let Some(holder) = &a.read_only {
+            a.overlay = Some(Overlay::Notice(Prompt::new(
+                format!("read-only, locked by {holder}"),
+                vec![('o', "ok")],
+            )));
+        }
         Ok(a)
     }
 
+    /// remembers the db as it is on disk now
+    fn synced(&mut self) -> Result<()> {
+        self.db_stamp = FileStamp::read(&profile().db_path);
+        self.db_base = serde_yaml::to_value(&self.content_register)?;
+        self.ignored_stamp = None;
+        self.journal.reset(&mut self.content_register);
+        Ok(())
+    }
+
+    /// Some if the db file was changed since the last load or save
+    fn external_change(&self) -> Option<FileStamp> {
+        FileStamp::changed(self.db_stamp.as_ref(), &profile().db_path)
+    }
+
+    fn check_external_change(&mut self) -> Result<()> {
+        if self.overlay.is_some() {
+            return Ok(());
+        }
+        if let Some(stamp) = self.external_change() {
+            // nothing to lose, follow the instance holding the lock
+            if self.read_only.is_some() {
+                return self.reload_db();
+            }
+            if self.ignored_stamp.as_ref() != Some(&stamp) {
+                self.ignored_stamp = Some(stamp);
+                self.overlay = Some(external_change_prompt(false));
+            }
+        }
+        Ok(())
+    }
+
+    /// throws away the local changes
+    fn reload_db(&mut self) -> Result<()> {
+        if let Some(db) = DBHandler::load()? {
+            self.content_register = db.register;
+            self.fix_stack();
+        }
+        // the journal might have been replayed over the file
+        self.unsaved = None;
+        if self.read_only.is_none() {
+            self.journal.snapshot()?;
+        }
+        self.synced()
+    }
+
+    /// three way merges the file on disk into the local changes and saves the result
+    fn merge_db(&mut self) -> Result<()> {
+        let theirs = match storage::open(&profile().db_path)?.load()? {
+            Some(db) => serde_yaml::to_value(&db.register)?,
+            None => return self.save_db(),
+        };
+        let ours = serde_yaml::to_value(&self.content_register)?;
+        let (merged, conflicts) = merge_registers(&self.db_base, &ours, &theirs)?;
+        self.content_register = serde_yaml::from_value(merged)?;
+        self.fix_stack();
+        self.unsaved = None;
+        self.save_db()?;
+        if !conflicts.is_empty() {
+            debug!("merge conflicts: {:?}", conflicts);
+            self.overlay = Some(Overlay::Notice(Prompt::new(
+                format!("{} conflicts, kept your version", conflicts.len()),
+                vec![('o', "ok")],
+            )));
+        }
+        Ok(())
+    }
+
+    fn fix_stack(&mut self) {
+        let register = &self.content_register;
+        if !self.stack.truncate_invalid(|id| register.get(id).is_some()) {
+            if let Some(mp) = db::main_provider(register) {
+                self.stack = ContentStack::new(mp);
+            }
+        }
+    }
+
+    /// saves the current db and loads the db of the other profile
+    pub fn switch_profile(&mut self, name: &str) -> Result<()> {
+        self.stop_pomodoro();
+        if self.read_only.is_none() {
+            self.save_db()?;
+        }
+        let old = profile().name.clone();
+        lock::release(&profile().db_path);
+        // the db is loaded from the active profile, so it is set first and put back if loading fails
+        match set_profile(name).and_then(|_| Self::load()) {
+            Ok(app) => *self = app,
+            Err(e) => {
+                lock::release(&profile().db_path);
+                set_profile(&old)?;
+                self.read_only = lock::acquire(&profile().db_path)?;
+                return Err(e.context(format!("failed to switch to profile {name}")));
+            }
+        }
+        Ok(())
+    }
+
     pub async fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
         let mut events = EventStream::new();
+        let mut reminders = tokio::time::interval(Duration::from_secs(30));
         loop {
             if self.quit {
                 return Ok(());
             }
+            self.check_external_change()?;
+            self.tick_pomodoro()?;
             terminal.draw(|f| self.render(f))?;
             let sleep = tokio::time::sleep(Duration::from_secs_f64(0.5));
             let event = events.next().fuse();
             select! {
-                Some(e) = event => self.handle_event(&e.unwrap())?,
+                Some(e) = event => {
+                    // a failed action is reported, the app keeps running
+                    if let Err(err) = self.handle_event(&e.unwrap()) {
+                        error!("{err:?}");
+                        self.status.error(format!("error: {err}"));
+                    }
+                }
+                _ = reminders.tick() => self.check_reminders(),
                 _ = sleep => (),
             }
         }
     }
 
-    pub fn save(self) -> Result<()> {
-        DBHandler {
-            register: self.content_register,
-            editor: self.editor,
+    /// nothing is saved when read-only
+    pub fn save(mut self) -> Result<()> {
+        self.stop_pomodoro();
+        match self.read_only {
+            Some(_) => Ok(()),
+            None => self.save_db(),
         }
-        .save()
+    }
+
+    /// starts a pomodoro on the selected todo, or stops the one running on it
+    fn toggle_pomodoro(&mut self) {
+        // the tracked time could not be kept
+        if self.read_only.is_some() {
+            self.status.error("read-only, can't start a pomodoro");
+            return;
+        }
+        let selected = self
+            .content_register
+            .get(self.stack.last())
+            .and_then(|e| e.as_provider())
+            .and_then(|e| e.get_selected())
+            .filter(|&id| {
+                self.content_register
+                    .get(id)
+                    .is_some_and(|c| c.as_any().is::<Todo>())
+            });
+        let id = match selected {
+            Some(id) => id,
+            None => return,
+        };
+        let same = self.pomodoro.as_ref().is_some_and(|s| s.todo == id);
+        self.stop_pomodoro();
+        if !same {
+            self.pomodoro = Some(Session::start(id, &mut self.content_register));
+        }
+    }
+
+    fn stop_pomodoro(&mut self) {
+        if let Some(s) = self.pomodoro.take() {
+            s.stop(&mut self.content_register);
+        }
+    }
+
+    fn tick_pomodoro(&mut self) -> Result<()> {
+        let session = match self.pomodoro.take() {
+            Some(s) => s,
+            None => return Ok(()),
+        };
+        let (session, message) = session.tick(&mut self.content_register);
+        self.pomodoro = session;
+        if let Some(message) = message {
+            let todo = self
+                .content_register
+                .get(self.pomodoro.as_ref().map_or(self.stack.last(), |s| s.todo))
+                .map(|c| c.as_display().text().into_owned())
+                .unwrap_or_default();
+            notify(&message, &todo);
+            self.record()?;
+        }
+        Ok(())
+    }
+
+    fn check_reminders(&mut self) {
+        for r in self.reminders.check(&self.content_register) {
+            notify(&r.title, &r.body);
+            self.status.info(r.line());
+        }
+    }
+
+    /// the pomodoro countdown, if one runs
+    fn header_line(&self) -> Option<Spans<'static>> {
+        let s = self.pomodoro.as_ref()?;
+        let style = match s.phase {
+            Phase::Work => theme().running,
+            Phase::ShortBreak | Phase::LongBreak => {
+                theme().done.remove_modifier(Modifier::CROSSED_OUT)
+            }
+        };
+        let todo = self
+            .content_register
+            .get(s.todo)
+            .map(|c| c.as_display().text().into_owned())
+            .unwrap_or_default();
+        Some(Spans::from(vec![
+            Span::styled(
+                format!("{} {}", s.phase.name(), format_clock(s.remaining())),
+                style,
+            ),
+            Span::styled(format!("  {todo}"), theme().text),
+            Span::styled(format!("  {} done", s.done), theme().dim),
+        ]))
+    }
+
+    /// journals what the last action changed. when read-only the change is refused, the db is read again
+    fn record(&mut self) -> Result<()> {
+        if self.read_only.is_some() {
+            let touched = self.content_register.take_touched();
+            if touched.iter().any(|(_, before, now)| before != now) {
+                self.reload_db()?;
+                self.status.error("read-only, the change was not kept");
+            }
+            return Ok(());
+        }
+        let changed = self.journal.record(&mut self.content_register)?;
+        if let Some(unsaved) = &mut self.unsaved {
+            unsaved.extend(changed);
+        }
+        Ok(())
+    }
+
+    pub fn save_db(&mut self) -> Result<()> {
+        if let Some(holder) = &self.read_only {
+            bail!("read-only, the db is locked by {holder}");
+        }
+        let mut storage = storage::open(&profile().db_path)?;
+        self.record()?;
+        // only the changes need writing if the db on disk is still the one of the last save
+        match self
+            .unsaved
+            .take()
+            .filter(|_| self.external_change().is_none())
+        {
+            Some(ids) => storage
+                .save_changes(&self.content_register, &ids.into_iter().collect::<Vec<_>>())?,
+            None => storage.save(&self.content_register)?,
+        }
+        self.journal.snapshot()?;
+        self.unsaved = Some(HashSet::new());
+        self.status.info("saved");
+        self.synced()
+    }
+
+    /// mode, current list, counts and the latest message on the left. pending keys and the running timer on the right
+    fn status_line(&mut self) -> (Spans<'static>, Spans<'static>) {
+        let content = self.content_register.get(self.stack.last()).unwrap();
+        let status = content.as_status();
+        let mut left = vec![
+            StatusBar::mode_span(status.map_or(Mode::Normal, |s| s.mode())),
+            Span::styled(format!(" {}", content.as_display().text()), theme().text),
+        ];
+        if self.read_only.is_some() {
+            left.push(Span::styled(" [read-only]", theme().error));
+        }
+        if let Some(counts) = status.map(|s| counts(&self.content_register, s.counted())) {
+            left.push(Span::styled(format!("  {counts}"), theme().dim));
+        }
+        let mut right = vec![];
+        if let Some(keys) = status.map(|s| s.pending_keys()).filter(|k| !k.is_empty()) {
+            right.push(Span::styled(format!("{keys}  "), theme().tag));
+        }
+        right.extend(self.timer_spans());
+        if let Some(message) = self.status.message() {
+            left.push(Span::raw("  "));
+            left.extend(StatusBar::message_spans(message).0);
+        }
+        (Spans::from(left), Spans::from(right))
+    }
+
+    fn timer_spans(&self) -> Vec<Span<'static>> {
+        let t = match running_timer(&self.content_register) {
+            Some((_, t)) => t,
+            None => return vec![],
+        };
+        let clock = t
+            .intervals
+            .last()
+            .map(|i| format_clock(i.duration()))
+            .unwrap_or_default();
+        vec![
+            Span::styled("● ", theme().running),
+            Span::styled(format!("{clock}  {}", t.content), theme().text),
+            Span::styled(
+                format!("  total {} ", format_duration(t.tracked())),
+                theme().dim,
+            ),
+        ]
     }
 
     pub fn render<B: Backend>(&mut self, f: &mut Frame<B>) {
-        let rect = f.size();
+        let mut rect = f.size();
+        if let Some(header) = self.header_line() {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(1)])
+                .split(rect);
+            rect = chunks[1];
+            f.render_widget(Paragraph::new(header), chunks[0]);
+        }
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(rect);
+        rect = chunks[0];
+        let (left, right) = self.status_line();
+        let bar = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(1), Constraint::Length(right.width() as u16)])
+            .split(chunks[1]);
+        f.render_widget(Paragraph::new(left), bar[0]);
+        f.render_widget(Paragraph::new(right), bar[1]);
         let id = self.stack.last();
         let ctx = (&(*self)).into();
         let lb = self
@@ -206,7 +607,7 @@ impl App {
         let mut i = SelectedIndex::none();
         let index = self
             .content_register
-            .get_mut(id)
+            .view_mut(id)
             .unwrap()
             .as_provider_mut()
             .map(|e| e.context_mut())
@@ -221,10 +622,227 @@ impl App {
                 },
             )
         });
+
+        if let Some(overlay) = self.overlay.as_mut() {
+            overlay.draw(f, rect);
+        }
+    }
+
+    fn handle_overlay_event(&mut self, event: &Event) -> Result<()> {
+        let overlay = match self.overlay.as_mut() {
+            Some(o) => o,
+            None => return Ok(()),
+        };
+        match overlay {
+            Overlay::ProfilePicker(p) => match p.handle_events(event, ()) {
+                PickerAction::None => (),
+                PickerAction::Cancel => self.overlay = None,
+                PickerAction::Pick(name) => {
+                    self.overlay = None;
+                    // switching saves, so outside changes have to be dealt with first
+                    if self.external_change().is_some() && self.read_only.is_none() {
+                        self.overlay = Some(external_change_prompt(false));
+                    } else if name != profile().name {
+                        self.switch_profile(&name)?;
+                    }
+                }
+            },
+            Overlay::ExternalChange { prompt, quitting } => {
+                let quitting = *quitting;
+                let pick = match prompt.handle_events(event, ()) {
+                    PromptAction::None => return Ok(()),
+                    PromptAction::Cancel => 'i',
+                    PromptAction::Pick(c) => c,
+                };
+                self.overlay = None;
+                match pick {
+                    'r' => self.reload_db()?,
+                    'm' => self.merge_db()?,
+                    'o' => self.save_db()?,
+                    _ => return Ok(()),
+                }
+                // a merge might still have conflicts to show
+                self.quit = quitting && self.overlay.is_none();
+            }
+            Overlay::History(h) => {
+                if h.handle_events(event, ()) {
+                    self.overlay = None;
+                }
+            }
+            Overlay::Notice(prompt) => match prompt.handle_events(event, ()) {
+                PromptAction::None => (),
+                _ => self.overlay = None,
+            },
+            Overlay::Palette(p) => match p.handle_events(event, ()) {
+                PaletteAction::None => (),
+                PaletteAction::Cancel => self.overlay = None,
+                PaletteAction::Run(line) => {
+                    self.overlay = None;
+                    self.run_command(&line)?;
+                }
+            },
+            Overlay::Search(s) => match s.handle_events(event, ()) {
+                SearchAction::None => (),
+                SearchAction::Cancel => self.overlay = None,
+                SearchAction::Pick(found) => {
+                    self.overlay = None;
+                    self.reveal(&found);
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// asks first if the db was changed outside, as quitting saves over it
+    fn quit_or_ask(&mut self) {
+        match self.external_change().filter(|_| self.read_only.is_none()) {
+            Some(_) => self.overlay = Some(external_change_prompt(true)),
+            None => self.quit = true,
+        }
+    }
+
+    fn open_palette(&mut self) {
+        let id = self.stack.last();
+        let mut commands = self
+            .content_register
+            .view_mut(id)
+            .and_then(|c| c.as_commands())
+            .map(|c| c.commands())
+            .unwrap_or_default();
+        commands.extend(GLOBAL_COMMANDS);
+        self.overlay = Some(Overlay::Palette(Palette::new(commands)));
+    }
+
+    fn open_search(&mut self) {
+        let found = tree::reachable(&self.content_register, self.stack.main_provider());
+        self.overlay = Some(Overlay::Search(Search::new(found)));
+    }
+
+    /// opens the widgets on the way to the found item and selects it
+    fn reveal(&mut self, found: &Found) {
+        self.stack = ContentStack::new(found.path[0]);
+        for &id in &found.path[1..] {
+            self.stack.push(id);
+        }
+        for (&widget, &item) in found.path.iter().zip(&found.selected) {
+            if let Some(p) = self
+                .content_register
+                .view_mut(widget)
+                .and_then(|c| c.as_provider_mut())
+            {
+                if let Some(i) = (0..).map_while(|i| p.get(i)).position(|id| id == item) {
+                    p.context_mut().select(i);
+                }
+            }
+        }
+    }
+
+    /// `name args` from the palette. global commands first, then the ones of the widget in view
+    fn run_command(&mut self, line: &str) -> Result<()> {
+        let (name, args) = line.split_once(' ').unwrap_or((line, ""));
+        let args = args.trim();
+        match name {
+            "" => (),
+            "quit" | "q" => self.quit_or_ask(),
+            "write" | "w" => match self.read_only {
+                Some(_) => self.status.error("read-only, nothing saved"),
+                None => self.save_db()?,
+            },
+            "profiles" => self.overlay = Some(Overlay::ProfilePicker(ProfilePicker::new())),
+            "history" => {
+                let days = match args {
+                    "" => 7,
+                    days => days.parse()?,
+                };
+                self.overlay = Some(Overlay::History(History::new(days)?));
+            }
+            "pomodoro" => self.toggle_pomodoro(),
+            "search" => self.open_search(),
+            _ => {
+                let id = self.stack.last();
+                let action = self
+                    .content_register
+                    .get_mut(id)
+                    .and_then(|c| c.as_commands())
+                    .and_then(|c| c.run_command(name, args, id));
+                match action {
+                    Some(a) => a.apply(&mut self.into())?,
+                    None => self.status.error(format!("unknown command: {name}")),
+                }
+            }
+        }
+        self.record()?;
+        Ok(())
+    }
+
+    /// clicks select, double clicks open, the wheel moves the selection and clicking the title goes back
+    fn handle_mouse(&mut self, mouse: &MouseEvent) -> Result<()> {
+        let (column, row) = (mouse.column, mouse.row);
+        let press = |code| key_press(code, KeyModifiers::NONE);
+        let id = self.stack.last();
+        let mode = self
+            .content_register
+            .get(id)
+            .and_then(|c| c.as_status())
+            .map_or(Mode::Normal, |s| s.mode());
+        // typing in the list is not interrupted
+        if self.overlay.is_none() && !matches!(mode, Mode::Normal | Mode::Visual) {
+            return Ok(());
+        }
+        match mouse.kind {
+            MouseEventKind::ScrollDown => return self.handle_event(&press(KeyCode::Down)),
+            MouseEventKind::ScrollUp => return self.handle_event(&press(KeyCode::Up)),
+            MouseEventKind::Down(MouseButton::Left) => (),
+            _ => return Ok(()),
+        }
+        let double = self
+            .last_click
+            .replace((Instant::now(), column, row))
+            .is_some_and(|(at, c, r)| at.elapsed() < DOUBLE_CLICK && (c, r) == (column, row));
+        if double {
+            self.last_click = None;
+        }
+
+        if let Some(overlay) = self.overlay.as_mut() {
+            let index = overlay.selected_index_mut();
+            if let Some(i) = index.item_at(column, row) {
+                index.select(i);
+                if double {
+                    return self.handle_overlay_event(&press(KeyCode::Enter));
+                }
+            }
+            return Ok(());
+        }
+        let index = match self
+            .content_register
+            .view_mut(id)
+            .and_then(|c| c.as_provider_mut())
+        {
+            Some(p) => p.context_mut(),
+            None => return Ok(()),
+        };
+        let action = match index.item_at(column, row) {
+            Some(i) => {
+                index.select(i);
+                match double {
+                    true => AppAction::MoveRight,
+                    false => AppAction::None,
+                }
+            }
+            None if index.title_at(column, row) => AppAction::MoveLeft,
+            None => AppAction::None,
+        };
+        action.apply(&mut self.into())
     }
 
     fn handle_event(&mut self, event: &Event) -> Result<()> {
         dbg!(event);
+        if let Event::Mouse(mouse) = event {
+            return self.handle_mouse(mouse);
+        }
+        if self.overlay.is_some() {
+            return self.handle_overlay_event(event);
+        }
         let id = self.stack.last();
         let a = self
             .content_register
@@ -238,15 +856,24 @@ impl App {
             EventAction::Unabsorbed(a) => {
                 match event {
                     Event::Key(k) => match k {
-                        key!('q') => {
-                            self.quit = true;
-                        }
+                        key!('q') => self.quit_or_ask(),
+                        key!(':') | shift!(':') | ctrl!('p') => self.open_palette(),
+                        ctrl!('f') => self.open_search(),
                         key!(Right) => {
                             AppAction::MoveRight.apply(&mut self.into())?;
                         }
                         key!(Left) => {
                             AppAction::MoveLeft.apply(&mut self.into())?;
                         }
+                        shift!('P') => {
+                            self.overlay = Some(Overlay::ProfilePicker(ProfilePicker::new()));
+                        }
+                        key!('p') => {
+                            self.toggle_pomodoro();
+                        }
+                        shift!('H') => {
+                            self.overlay = Some(Overlay::History(History::new(7)?));
+                        }
                         _ => {}
                     },
                     _ => {}
@@ -256,6 +883,84 @@ impl App {
             EventAction::Absorbed(a) => a,
         };
 
-        a.apply(&mut self.into())
+        a.apply(&mut self.into())?;
+        self.record()?;
+        Ok(())
+    }
+}
+
+/// the longest time between the clicks of a double click
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+const GLOBAL_COMMANDS: [CommandInfo; 6] = [
+    CommandInfo {
+        name: "search",
+        args: "",
+        description: "find a list or todo anywhere (ctrl-f)",
+    },
+    CommandInfo {
+        name: "write",
+        args: "",
+        description: "save the db",
+    },
+    CommandInfo {
+        name: "quit",
+        args: "",
+        description: "save and quit (q)",
+    },
+    CommandInfo {
+        name: "profiles",
+        args: "",
+        description: "switch to another profile (P)",
+    },
+    CommandInfo {
+        name: "history",
+        args: "[days]",
+        description: "what changed in the last days (H)",
+    },
+    CommandInfo {
+        name: "pomodoro",
+        args: "",
+        description: "start or stop a pomodoro on the selected todo (p)",
+    },
+];
+
+/// pending/done/total of the todos among the ids, or just how many there are
+fn counts(register: &ContentRegister<Content, Id>, ids: &[Id]) -> String {
+    let todos = ids
+        .iter()
+        .filter_map(|&id| register.get(id))
+        .filter_map(|c| c.as_any().downcast_ref::<Todo>())
+        .collect::<Vec<_>>();
+    if todos.is_empty() {
+        return format!("{} items", ids.len());
+    }
+    let pending = todos
+        .iter()
+        .filter(|t| t.status == TodoStatus::Pending)
+        .count();
+    let done = todos
+        .iter()
+        .filter(|t| t.status == TodoStatus::Done)
+        .count();
+    format!("{pending} pending · {done} done · {} total", ids.len())
+}
+
+fn external_change_prompt(quitting: bool) -> Overlay {
+    let (reload, ignore) = match quitting {
+        true => ("discard my changes", "cancel"),
+        false => ("reload from disk", "ignore for now"),
+    };
+    Overlay::ExternalChange {
+        prompt: Prompt::new(
+            "db changed outside of wildo",
+            vec![
+                ('r', reload),
+                ('m', "merge"),
+                ('o', "overwrite with mine"),
+                ('i', ignore),
+            ],
+        ),
+        quitting,
     }
 }
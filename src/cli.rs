@@ -0,0 +1,323 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use crate::{
+    content::{main_provider::MainProvider, todo_list::TodoList},
+    register::Id,
+    service::{
+        config::profile, csv, db::DBHandler, editors::Yank, ical, journal, lock, markdown, merge,
+        remind, storage, tree,
+    },
+};
+
+pub const USAGE: &str = "\
+usage: wildo [--profile <name>] [command]
+    wildo
+    wildo export markdown|ics|csv [--list <title>] [-o <file>]
+    wildo import markdown <file>
+    wildo import ics <file> --list <title>
+    wildo migrate <from> <to>
+    wildo merge <base> <ours> <theirs> [-o <file>]
+    wildo log [--days <n>]
+    wildo remind [--daemon]";
+
+/// pulls `--profile <name>` out of the args. falls back to $WILDO_PROFILE
+pub fn take_profile(args: Vec<String>) -> Result<(Option<String>, Vec<String>)> {
+    let mut profile = None;
+    let mut rest = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--profile" | "-p" => {
+                profile = Some(
+                    args.next()
+                        .ok_or(anyhow!("missing profile name\n{USAGE}"))?,
+                )
+            }
+            _ => rest.push(arg),
+        }
+    }
+    Ok((profile.or(std::env::var("WILDO_PROFILE").ok()), rest))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Format {
+    Markdown,
+    Ics,
+    /// tracked time, export only
+    Csv,
+}
+impl std::str::FromStr for Format {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "markdown" | "md" => Ok(Self::Markdown),
+            "ics" | "ical" => Ok(Self::Ics),
+            "csv" => Ok(Self::Csv),
+            _ => Err(anyhow!("unknown format: {s}")),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Command {
+    Tui,
+    Export {
+        format: Format,
+        list: Option<String>,
+        out: Option<PathBuf>,
+    },
+    Import {
+        format: Format,
+        file: PathBuf,
+        list: Option<String>,
+    },
+    /// copy the db from one storage to another (*.yaml, *.json, *.sqlite)
+    Migrate {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    /// three way merge of db files, written to ours unless out is given. fails if there are conflicts,
+    /// so it can be used as a git merge driver
+    Merge {
+        base: PathBuf,
+        ours: PathBuf,
+        theirs: PathBuf,
+        out: Option<PathBuf>,
+    },
+    /// the journal of the last days
+    Log {
+        days: i64,
+    },
+    /// lists what is due soon or overdue. the daemon keeps running and notifies instead
+    Remind {
+        daemon: bool,
+    },
+}
+
+impl Command {
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self> {
+        let cmd = match args.next() {
+            Some(cmd) => cmd,
+            None => return Ok(Self::Tui),
+        };
+        if cmd == "migrate" {
+            let mut path = || {
+                args.next()
+                    .map(PathBuf::from)
+                    .ok_or(anyhow!("missing path\n{USAGE}"))
+            };
+            return Ok(Self::Migrate {
+                from: path()?,
+                to: path()?,
+            });
+        }
+        if cmd == "log" {
+            let days = match (args.next().as_deref(), args.next()) {
+                (None, _) => 7,
+                (Some("--days" | "-d"), Some(n)) => n.parse()?,
+                (Some(arg), _) => bail!("unexpected argument: {arg}\n{USAGE}"),
+            };
+            return Ok(Self::Log { days });
+        }
+        if cmd == "remind" {
+            let daemon = match args.next().as_deref() {
+                None => false,
+                Some("--daemon" | "-d") => true,
+                Some(arg) => bail!("unexpected argument: {arg}\n{USAGE}"),
+            };
+            return Ok(Self::Remind { daemon });
+        }
+        if cmd == "merge" {
+            let mut paths = vec![];
+            let mut out = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--out" | "-o" => out = args.next().map(PathBuf::from),
+                    _ if paths.len() < 3 && !arg.starts_with('-') => paths.push(PathBuf::from(arg)),
+                    _ => bail!("unexpected argument: {arg}\n{USAGE}"),
+                }
+            }
+            let [base, ours, theirs]: [PathBuf; 3] = paths
+                .try_into()
+                .map_err(|_| anyhow!("merge needs base, ours and theirs\n{USAGE}"))?;
+            return Ok(Self::Merge {
+                base,
+                ours,
+                theirs,
+                out,
+            });
+        }
+        let format = args
+            .next()
+            .ok_or(anyhow!("missing format\n{USAGE}"))?
+            .parse()?;
+        let mut list = None;
+        let mut out = None;
+        let mut file = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--list" | "-l" => list = args.next(),
+                "--out" | "-o" => out = args.next().map(PathBuf::from),
+                _ if file.is_none() && !arg.starts_with('-') => file = Some(PathBuf::from(arg)),
+                _ => bail!("unexpected argument: {arg}\n{USAGE}"),
+            }
+        }
+        let cmd = match cmd.as_str() {
+            "export" => Self::Export { format, list, out },
+            "import" => Self::Import {
+                format,
+                file: file.ok_or(anyhow!("missing file\n{USAGE}"))?,
+                list,
+            },
+            _ => bail!("unknown command: {cmd}\n{USAGE}"),
+        };
+        Ok(cmd)
+    }
+
+    /// runs the command without the tui
+    pub fn run(self) -> Result<()> {
+        match self {
+            Self::Tui | Self::Remind { daemon: true } => unreachable!(),
+            Self::Export { format, list, out } => {
+                let db = DBHandler::load()?.unwrap_or(DBHandler::new());
+                let mp = db.main_provider().context("no main provider in db")?;
+                let list = list
+                    .map(|title| {
+                        find_list(&db, mp, &title)?.ok_or(anyhow!("no list named {title}"))
+                    })
+                    .transpose()?;
+                let text = match format {
+                    Format::Markdown => match list {
+                        Some(id) => markdown::export_list(&db.register, id),
+                        None => markdown::export_main(&db.register, mp),
+                    },
+                    Format::Ics => {
+                        let lists = match list {
+                            Some(id) => vec![id],
+                            None => main_items(&db, mp),
+                        };
+                        ical::export(&db.register, &lists)
+                    }
+                    Format::Csv => {
+                        let lists = match list {
+                            Some(id) => vec![id],
+                            None => main_items(&db, mp),
+                        };
+                        csv::export_intervals(&db.register, &lists)
+                    }
+                };
+                match out {
+                    Some(path) => fs::write(path, text)?,
+                    None => io::stdout().write_all(text.as_bytes())?,
+                }
+            }
+            Self::Import { format, file, list } => {
+                let mut db = DBHandler::load()?.unwrap_or(DBHandler::new());
+                if let Some(holder) = lock::holder(&profile().db_path) {
+                    bail!("db is locked by {holder}");
+                }
+                let mp = db.main_provider().context("no main provider in db")?;
+                let text = fs::read_to_string(&file)?;
+                match format {
+                    Format::Markdown => {
+                        let title = file
+                            .file_stem()
+                            .map(|s| s.to_string_lossy().into_owned())
+                            .unwrap_or_default();
+                        for id in markdown::import(&mut db.register, &text, &title) {
+                            add_to_main(&mut db, mp, id);
+                        }
+                    }
+                    Format::Ics => {
+                        let title = list.ok_or(anyhow!("ics import needs a --list\n{USAGE}"))?;
+                        let id = match find_list(&db, mp, &title)? {
+                            Some(id) => id,
+                            None => {
+                                let id = db.register.alloc(TodoList::new(title));
+                                add_to_main(&mut db, mp, id);
+                                id
+                            }
+                        };
+                        let (created, updated) = ical::import(&mut db.register, &text, id);
+                        println!("{created} created, {updated} updated");
+                    }
+                    Format::Csv => bail!("csv can only be exported"),
+                }
+                db.save()?;
+            }
+            Self::Migrate { from, to } => {
+                let db = DBHandler::load_from(&from)?.context("nothing to migrate")?;
+                db.save_to(&to)?;
+            }
+            Self::Merge {
+                base,
+                ours,
+                theirs,
+                out,
+            } => {
+                let out = out.unwrap_or(ours.clone());
+                let conflicts = merge::merge_files(&base, &ours, &theirs, &out)?;
+                for c in &conflicts {
+                    eprintln!("conflict: {c}");
+                }
+                if !conflicts.is_empty() {
+                    bail!("{} conflicts, kept ours for them", conflicts.len());
+                }
+            }
+            Self::Log { days } => {
+                for e in journal::recent(&profile().db_path, days)? {
+                    println!("{}", e.line());
+                }
+            }
+            Self::Remind { daemon: false } => {
+                // no DBHandler::load, listing should not take the lock
+                if let Some(db) = storage::open_read_only(&profile().db_path)?.load()? {
+                    for r in remind::upcoming(&db.register) {
+                        println!("{}", r.line());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// the list shown from the main provider with this title, subtask lists don't count. fails if several are
+fn find_list(db: &DBHandler, mp: Id, title: &str) -> Result<Option<Id>> {
+    let lists = tree::lists_called(&db.register, mp, title);
+    match lists.as_slice() {
+        [] => Ok(None),
+        [(id, _)] => Ok(Some(*id)),
+        _ => bail!("{} lists are called {title}", lists.len()),
+    }
+}
+
+fn main_items(db: &DBHandler, mp: Id) -> Vec<Id> {
+    db.register
+        .get(mp)
+        .unwrap()
+        .as_any()
+        .downcast_ref::<MainProvider>()
+        .unwrap()
+        .items()
+        .to_vec()
+}
+
+fn add_to_main(db: &mut DBHandler, mp: Id, id: Id) {
+    let mp = db.register.get_mut(mp).unwrap();
+    let pos = mp
+        .as_any()
+        .downcast_ref::<MainProvider>()
+        .unwrap()
+        .items()
+        .len();
+    mp.as_yankdest().unwrap().insert(Yank { id, pos });
+}
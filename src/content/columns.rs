@@ -0,0 +1,294 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use tui::{
+    style::Style,
+    text::Span,
+    widgets::{Block, Borders},
+};
+
+use crate::{
+    display::{Item, Line, ListBuilder, SelectedText},
+    register::{ContentRegister, Id},
+    service::theme::theme,
+    traits::Display,
+};
+
+use super::{
+    todo::{Todo, TodoStatus},
+    todo_list::TodoList,
+    traits::Content,
+};
+
+/// what a column of a todo list shows of each todo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Column {
+    Title,
+    Due,
+    Priority,
+    Tags,
+    Created,
+    Completed,
+    /// tracked time and pomodoros
+    Tracked,
+    /// done subtasks out of all of them
+    Progress,
+}
+
+const COLUMNS: [(Column, &str); 8] = [
+    (Column::Title, "title"),
+    (Column::Due, "due"),
+    (Column::Priority, "priority"),
+    (Column::Tags, "tags"),
+    (Column::Created, "created"),
+    (Column::Completed, "completed"),
+    (Column::Tracked, "tracked"),
+    (Column::Progress, "progress"),
+];
+
+impl Column {
+    pub fn name(self) -> &'static str {
+        COLUMNS.iter().find(|(c, _)| *c == self).unwrap().1
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.to_lowercase();
+        COLUMNS.iter().find(|(_, n)| *n == text).map(|(c, _)| *c)
+    }
+
+    /// shown on the border. the title column has the title of the list instead
+    fn title(self) -> &'static str {
+        match self {
+            Self::Title => "Title",
+            Self::Due => "Due Date",
+            Self::Priority => "Priority",
+            Self::Tags => "Tags",
+            Self::Created => "Created",
+            Self::Completed => "Completed",
+            Self::Tracked => "Tracked",
+            Self::Progress => "Progress",
+        }
+    }
+
+    fn default_width(self) -> u16 {
+        match self {
+            Self::Title => 2,
+            _ => 1,
+        }
+    }
+
+    /// the text of the column for the todo and its style
+    fn cell(
+        self,
+        todo: &Todo,
+        register: &ContentRegister<Content, Id>,
+        now: NaiveDateTime,
+    ) -> (String, Style) {
+        let date = |d: Option<DateTime<Local>>| {
+            d.map(|d| d.format("%d-%m-%Y").to_string())
+                .unwrap_or_default()
+        };
+        match self {
+            Self::Title => (todo.text().into_owned(), theme().pending),
+            // done and ignored todos are not urgent anymore
+            Self::Due => (
+                todo.due_text(now.date()).unwrap_or_default(),
+                todo.urgency(now).map_or(theme().dim, |u| u.style()),
+            ),
+            Self::Priority => todo.priority.map_or((String::new(), theme().dim), |p| {
+                (p.symbol().to_owned(), p.style())
+            }),
+            Self::Tags => (todo.tags_text(), theme().tag),
+            Self::Created => (date(todo.created_at), theme().dim),
+            Self::Completed => (date(todo.completed_at), theme().dim),
+            Self::Tracked => (
+                todo.tracked_text().unwrap_or_default(),
+                match todo.is_tracking() {
+                    true => theme().running,
+                    false => theme().dim,
+                },
+            ),
+            Self::Progress => {
+                let subtasks = todo
+                    .subtasks
+                    .and_then(|id| register.get(id))
+                    .and_then(|c| c.as_any().downcast_ref::<TodoList>())
+                    .map(|l| {
+                        l.items()
+                            .iter()
+                            .filter_map(|&id| register.get(id))
+                            .filter_map(|c| c.as_any().downcast_ref::<Todo>())
+                            .filter(|t| t.status != TodoStatus::Ignored)
+                            .map(|t| t.status == TodoStatus::Done)
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                match subtasks.len() {
+                    0 => (String::new(), theme().dim),
+                    n => {
+                        let done = subtasks.iter().filter(|&&d| d).count();
+                        let st = match done == n {
+                            true => theme().text,
+                            false => theme().dim,
+                        };
+                        (format!("{done}/{n}"), st)
+                    }
+                }
+            }
+        }
+    }
+
+    /// the column with a cell for each of the todos
+    pub fn list(
+        self,
+        items: &[Id],
+        register: &ContentRegister<Content, Id>,
+        now: NaiveDateTime,
+    ) -> ListBuilder<'static> {
+        let mut list = ListBuilder::default();
+        list.block(
+            Block::default()
+                .title(Span::styled(self.title(), theme().title))
+                .border_style(theme().border)
+                .borders(Borders::all()),
+        );
+        list.items = items
+            .iter()
+            .map(|&id| {
+                register
+                    .get(id)
+                    .and_then(|c| c.as_any().downcast_ref::<Todo>())
+                    .map_or((String::new(), theme().dim), |t| {
+                        self.cell(t, register, now)
+                    })
+            })
+            .map(|(text, st)| {
+                let mut l = Line::new(Span::raw(text));
+                l.text_style(st);
+                Item {
+                    text: vec![l],
+                    selected_text: SelectedText::Style(st.patch(theme().selected)),
+                }
+            })
+            .collect();
+        list
+    }
+}
+
+/// a column of a list and how wide it is next to the others
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ListColumn {
+    pub column: Column,
+    pub width: u16,
+}
+
+impl ListColumn {
+    /// title and due date, 2 to 1
+    pub fn defaults() -> Vec<Self> {
+        [Column::Title, Column::Due]
+            .into_iter()
+            .map(|column| Self {
+                column,
+                width: column.default_width(),
+            })
+            .collect()
+    }
+
+    /// eg "title:3 due tags:2". the width is relative to the others, 2 for the title and 1 for the rest if not given
+    pub fn parse_all(text: &str) -> Result<Vec<Self>> {
+        let mut columns: Vec<Self> = vec![];
+        for word in text.split_whitespace() {
+            let (name, width) = word.split_once(':').unwrap_or((word, ""));
+            let column = match Column::parse(name) {
+                Some(c) => c,
+                None => bail!(
+                    "no column named {name}, the columns are {}",
+                    COLUMNS.map(|(_, n)| n).join(", ")
+                ),
+            };
+            let width = match width {
+                "" => column.default_width(),
+                w => match w.parse() {
+                    Ok(w) if w > 0 => w,
+                    _ => bail!("{name}: the width should be a number above 0, not {w}"),
+                },
+            };
+            if columns.iter().any(|c| c.column == column) {
+                bail!("{name} is there twice");
+            }
+            columns.push(Self { column, width });
+        }
+        if !columns.iter().any(|c| c.column == Column::Title) {
+            bail!("the title column has to be there");
+        }
+        Ok(columns)
+    }
+
+    /// the other way around from parse_all
+    pub fn format_all(columns: &[Self]) -> String {
+        columns
+            .iter()
+            .map(|c| format!("{}:{}", c.column.name(), c.width))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(column: Column, width: u16) -> ListColumn {
+        ListColumn { column, width }
+    }
+
+    #[test]
+    fn parse_all_reads_names_and_widths() {
+        assert_eq!(
+            ListColumn::parse_all("title:3 due Tags:2").unwrap(),
+            [
+                column(Column::Title, 3),
+                column(Column::Due, 1),
+                column(Column::Tags, 2)
+            ]
+        );
+        assert_eq!(
+            ListColumn::parse_all("priority title").unwrap(),
+            [column(Column::Priority, 1), column(Column::Title, 2)]
+        );
+    }
+
+    #[test]
+    fn parse_all_is_the_other_way_around_from_format_all() {
+        let columns = ListColumn::parse_all("title:3 progress created:2").unwrap();
+        assert_eq!(
+            ListColumn::parse_all(&ListColumn::format_all(&columns)).unwrap(),
+            columns
+        );
+        assert_eq!(
+            ListColumn::format_all(&ListColumn::defaults()),
+            "title:2 due:1"
+        );
+    }
+
+    #[test]
+    fn parse_all_refuses_bad_columns() {
+        let err = |text| ListColumn::parse_all(text).unwrap_err().to_string();
+        assert!(err("title owner").starts_with("no column named owner, the columns are title, due"));
+        assert_eq!(
+            err("title due:0"),
+            "due: the width should be a number above 0, not 0"
+        );
+        assert_eq!(
+            err("title:x"),
+            "title: the width should be a number above 0, not x"
+        );
+        assert_eq!(err("title due due:2"), "due is there twice");
+        assert_eq!(err("due tags"), "the title column has to be there");
+        assert_eq!(err(""), "the title column has to be there");
+    }
+}
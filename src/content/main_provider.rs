@@ -1,7 +1,7 @@
 #[allow(unused_imports)]
 use crate::{dbg, debug, error};
 
-use crossterm::event::{Event, KeyCode};
+use crossterm::event::{Event, KeyCode, KeyModifiers};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use tui::{
@@ -19,11 +19,17 @@ use crate::{
     service::{
         editors::{Edit, Yank},
         insert_mode::{InsertAction, InsertMode},
+        theme::theme,
+    },
+    shift,
+    traits::{
+        key_press, CommandInfo, Commands, Display, EventAction, EventHandler, Mode, Provider,
+        SelectedIndex, Status, Widget, YankDest,
     },
-    traits::{Display, EventAction, EventHandler, Provider, SelectedIndex, Widget, YankDest},
 };
 
 use super::{
+    statistics::Statistics,
     todo_list::TodoList,
     traits::{impliment_content, Container, Content, ContentTrait, DisplayContext, WidgetOutput},
 };
@@ -59,6 +65,10 @@ impl MainProvider {
             listen_target: Default::default(),
         }
     }
+
+    pub fn items(&self) -> &[Id] {
+        &self.container.items
+    }
 }
 
 impl<'a> EventHandler<'a> for MainProvider {
@@ -78,7 +88,7 @@ impl<'a> EventHandler<'a> for MainProvider {
                     id,
                     pos: me.container.selected_index.selected_index(),
                 });
-                ctx.register.unregister(id);
+                ctx.register.remove(id);
                 Ok(AppAction::None)
             }),
         };
@@ -171,6 +181,39 @@ impl<'a> EventHandler<'a> for MainProvider {
                     };
                     return EventAction::Absorbed(a.chain([add_action]));
                 }
+                shift!('S') => {
+                    let add_action = AppAction::Callback {
+                        call: Box::new(move |ctx| {
+                            let exists = ctx
+                                .register
+                                .iter()
+                                .any(|(_, c)| c.as_any().is::<Statistics>());
+                            if exists {
+                                return Ok(AppAction::None);
+                            }
+                            let id = ctx.register.alloc(Statistics::new());
+                            let me = ctx
+                                .register
+                                .get_mut(self_id)
+                                .unwrap()
+                                .as_any_mut()
+                                .downcast_mut::<Self>()
+                                .unwrap();
+                            let y = Yank {
+                                id,
+                                pos: me.container.items.len(),
+                            };
+                            me.insert(y);
+                            me.container.selected_index.select(y.pos);
+                            ctx.editor.edit_stack.push(Edit::Pasted {
+                                source: self_id,
+                                yanks: vec![y],
+                            });
+                            Ok(AppAction::None)
+                        }),
+                    };
+                    return EventAction::Absorbed(a.chain([add_action]));
+                }
                 key!('i') => {
                     let id = self.container.items[self.container.selected_index.selected_index()];
                     let action = AppAction::Callback {
@@ -231,9 +274,10 @@ impl<'a> Widget<'a> for MainProvider {
     type Output = WidgetOutput<'static>; // ? maybe try use GAT here
     fn display(&self, context: Self::Context) -> Self::Output {
         let mut content = ListBuilder::default();
+        content.set_numbered(true);
         content.title(Span::raw(self.title.clone())).block(
             Block::default()
-                .border_style(Style::default().fg(Color::Rgb(150, 150, 150)))
+                .border_style(theme().border)
                 .borders(Borders::all()),
         );
         content.items = self
@@ -267,11 +311,11 @@ impl Display for MainProvider {
     }
     fn display(&self) -> Self::Output {
         let mut text = Line::new(Span::raw(self.text()));
-        let st = Style::default().fg(Color::Rgb(200, 200, 100));
+        let st = theme().text;
         text.text_style(st);
         Item {
             text: vec![text],
-            selected_text: SelectedText::Style(st.add_modifier(Modifier::BOLD)),
+            selected_text: SelectedText::Style(st.patch(theme().selected)),
         }
     }
     fn set_text(&mut self, name: Cow<'static, str>) {
@@ -306,7 +350,68 @@ impl<'a> Provider<'a> for MainProvider {
     }
 }
 
+impl Commands for MainProvider {
+    fn commands(&self) -> Vec<CommandInfo> {
+        vec![
+            CommandInfo {
+                name: "add",
+                args: "",
+                description: "add a list below the selected one (a)",
+            },
+            CommandInfo {
+                name: "edit",
+                args: "",
+                description: "rename the selected list (i)",
+            },
+            CommandInfo {
+                name: "stats",
+                args: "",
+                description: "add the statistics page (S)",
+            },
+        ]
+    }
+
+    fn run_command(&mut self, name: &str, _args: &str, self_id: Id) -> Option<AppAction> {
+        let event = match name {
+            "add" => key_press(KeyCode::Char('a'), KeyModifiers::NONE),
+            "edit" if self.get_selected().is_some() => {
+                key_press(KeyCode::Char('i'), KeyModifiers::NONE)
+            }
+            "edit" => return Some(AppAction::error("no list selected")),
+            "stats" => key_press(KeyCode::Char('S'), KeyModifiers::SHIFT),
+            _ => return None,
+        };
+        match self.handle_events(&event, self_id) {
+            EventAction::Absorbed(a) | EventAction::Unabsorbed(a) => Some(a),
+        }
+    }
+}
+
+impl Status for MainProvider {
+    fn mode(&self) -> Mode {
+        match self.insert_mode.is_listening() {
+            true => Mode::Insert,
+            false => Mode::Normal,
+        }
+    }
+    fn pending_keys(&self) -> String {
+        self.container.pending.clone()
+    }
+    fn counted(&self) -> &[Id] {
+        self.items()
+    }
+}
+
 #[typetag::serde]
 impl ContentTrait for MainProvider {
-    impliment_content!(TodoList, Widget, EventHandler, Display, YankDest, Provider);
+    impliment_content!(
+        TodoList,
+        Widget,
+        EventHandler,
+        Display,
+        YankDest,
+        Provider,
+        Status,
+        Commands
+    );
 }
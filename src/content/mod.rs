@@ -1,4 +1,6 @@
+pub mod columns;
 pub mod main_provider;
+pub mod statistics;
 pub mod todo;
 pub mod todo_list;
 pub mod traits;
@@ -0,0 +1,269 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, collections::BTreeMap};
+use tui::{
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders},
+};
+
+use crate::{
+    display::{Item, Line, ListBuilder, SelectedText},
+    register::{ContentRegister, Id},
+    service::{theme::theme, tree},
+    traits::{Display, Mode, Provider, SelectedIndex, Status, Widget},
+};
+
+use super::{
+    todo::{format_duration, Todo, TodoStatus},
+    todo_list::TodoList,
+    traits::{impliment_content, Content, ContentTrait, DisplayContext, WidgetOutput},
+};
+
+/// how much got done. computed from the todos every time it is shown
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Statistics {
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    selected_index: SelectedIndex,
+}
+
+impl Statistics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Stats {
+    /// completed todos per day
+    pub completed: BTreeMap<NaiveDate, usize>,
+    /// from created_at to completed_at
+    pub average_time_to_complete: Option<Duration>,
+    /// (list title, pending todos past their due date)
+    pub overdue: Vec<(String, usize)>,
+    /// days in a row up to today (or yesterday) with something completed
+    pub streak: usize,
+    /// finished pomodoro work phases per day
+    pub pomodoros: BTreeMap<NaiveDate, usize>,
+}
+
+impl Stats {
+    pub fn compute(register: &ContentRegister<Content, Id>) -> Self {
+        let now = Local::now().naive_local();
+        let today = now.date();
+        let mut stats = Self::default();
+        // deleted todos and lists are not counted
+        let shown = tree::reachable_ids(register);
+
+        let mut durations = vec![];
+        for c in shown.iter().filter_map(|&id| register.get(id)) {
+            let t = match c.as_any().downcast_ref::<Todo>() {
+                Some(t) => t,
+                None => continue,
+            };
+            if let (TodoStatus::Done, Some(done)) = (t.status, t.completed_at) {
+                *stats
+                    .completed
+                    .entry(done.naive_local().date())
+                    .or_default() += 1;
+                if let Some(created) = t.created_at {
+                    durations.push(done - created);
+                }
+            }
+            for p in &t.pomodoros {
+                *stats.pomodoros.entry(p.naive_local().date()).or_default() += 1;
+            }
+        }
+        if !durations.is_empty() {
+            let total = durations.iter().fold(Duration::zero(), |a, &d| a + d);
+            stats.average_time_to_complete = Some(total / durations.len() as i32);
+        }
+
+        for c in shown.iter().filter_map(|&id| register.get(id)) {
+            let list = match c.as_any().downcast_ref::<TodoList>() {
+                Some(list) => list,
+                None => continue,
+            };
+            let overdue = list
+                .items()
+                .iter()
+                .filter_map(|&id| register.get(id))
+                .filter_map(|c| c.as_any().downcast_ref::<Todo>())
+                .filter(|t| t.status == TodoStatus::Pending && t.due().is_some_and(|d| d < now))
+                .count();
+            if overdue > 0 {
+                stats
+                    .overdue
+                    .push((c.as_display().text().into_owned(), overdue));
+            }
+        }
+        stats.overdue.sort();
+
+        let mut day = today;
+        if !stats.completed.contains_key(&day) {
+            day = day.pred();
+        }
+        while stats.completed.contains_key(&day) {
+            stats.streak += 1;
+            day = day.pred();
+        }
+        stats
+    }
+
+    pub fn completed_on(&self, day: NaiveDate) -> usize {
+        self.completed.get(&day).copied().unwrap_or(0)
+    }
+
+    /// completed in the week (starting monday) containing day
+    pub fn completed_in_week(&self, day: NaiveDate) -> usize {
+        in_week(&self.completed, day)
+    }
+
+    pub fn pomodoros_on(&self, day: NaiveDate) -> usize {
+        self.pomodoros.get(&day).copied().unwrap_or(0)
+    }
+
+    pub fn pomodoros_in_week(&self, day: NaiveDate) -> usize {
+        in_week(&self.pomodoros, day)
+    }
+}
+
+fn in_week(per_day: &BTreeMap<NaiveDate, usize>, day: NaiveDate) -> usize {
+    let start = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+    per_day
+        .range(start..start + Duration::days(7))
+        .map(|(_, n)| n)
+        .sum()
+}
+
+fn bar(n: usize) -> String {
+    "█".repeat(n.min(30))
+}
+
+impl<'a> Widget<'a> for Statistics {
+    type Context = DisplayContext<'a>;
+    type Output = WidgetOutput<'static>;
+    fn display(&self, context: Self::Context) -> Self::Output {
+        let stats = Stats::compute(context.content_register);
+        let today = Local::now().naive_local().date();
+
+        let mut lines = vec![];
+        lines.push("completed per day".to_owned());
+        for i in (0..7).rev() {
+            let day = today - Duration::days(i);
+            let n = stats.completed_on(day);
+            lines.push(format!("  {} {:>3} {}", day.format("%a %d-%m"), n, bar(n)));
+        }
+        lines.push(String::new());
+        lines.push("completed per week".to_owned());
+        for i in (0..4).rev() {
+            let day = today - Duration::weeks(i);
+            let n = stats.completed_in_week(day);
+            lines.push(format!(
+                "  week {:<5} {:>3} {}",
+                day.format("%V"),
+                n,
+                bar(n)
+            ));
+        }
+        lines.push(String::new());
+        lines.push(format!(
+            "average time to complete: {}",
+            stats
+                .average_time_to_complete
+                .map(format_duration)
+                .unwrap_or("-".to_owned())
+        ));
+        lines.push(format!("streak: {} days", stats.streak));
+        lines.push(format!(
+            "pomodoros: {} today, {} this week",
+            stats.pomodoros_on(today),
+            stats.pomodoros_in_week(today)
+        ));
+        lines.push(String::new());
+        lines.push("overdue".to_owned());
+        if stats.overdue.is_empty() {
+            lines.push("  nothing".to_owned());
+        }
+        let overdue = lines.len();
+        for (list, n) in &stats.overdue {
+            lines.push(format!("  {list}: {n}"));
+        }
+
+        let mut content = ListBuilder::default();
+        content.title(Span::raw("Statistics")).block(
+            Block::default()
+                .border_style(theme().border)
+                .borders(Borders::all()),
+        );
+        content.items = lines
+            .into_iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let st = match i >= overdue {
+                    true => theme().overdue,
+                    false => theme().text,
+                };
+                let mut line = Line::new(Span::raw(text));
+                line.text_style(st);
+                Item {
+                    text: vec![line],
+                    selected_text: SelectedText::Style(st),
+                }
+            })
+            .collect();
+        WidgetOutput::MainProvider { content }
+    }
+}
+
+impl Display for Statistics {
+    type Output = Item<'static>;
+    fn text(&self) -> Cow<'static, str> {
+        Cow::from("Statistics")
+    }
+    fn display(&self) -> Self::Output {
+        let mut text = Line::new(Span::raw(self.text()));
+        let st = theme().tag;
+        text.text_style(st);
+        Item {
+            text: vec![text],
+            selected_text: SelectedText::Style(st.patch(theme().selected)),
+        }
+    }
+    fn set_text(&mut self, _name: Cow<'static, str>) {}
+}
+
+/// there is nothing to select, only the rendering needs the index
+impl<'a> Provider<'a> for Statistics {
+    type Context = &'a mut SelectedIndex;
+    type Item = Option<Id>;
+    fn get(&self, _index: usize) -> Self::Item {
+        None
+    }
+    fn context_mut(&'a mut self) -> Self::Context {
+        &mut self.selected_index
+    }
+    fn get_selected(&self) -> Self::Item {
+        None
+    }
+}
+
+impl Status for Statistics {
+    fn mode(&self) -> Mode {
+        Mode::Normal
+    }
+    fn pending_keys(&self) -> String {
+        String::new()
+    }
+    fn counted(&self) -> &[Id] {
+        &[]
+    }
+}
+
+#[typetag::serde]
+impl ContentTrait for Statistics {
+    impliment_content!(Statistics, Widget, Display, Provider, Status);
+}
@@ -1,7 +1,7 @@
 #[allow(unused_imports)]
 use crate::{dbg, debug, error};
 
-use chrono::{Datelike, NaiveDate};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use tui::{
@@ -10,12 +10,14 @@ use tui::{
 };
 
 use crate::{
-    display::{Item, Line, SelectedText},
+    display::{Item, Line, Marker, MarkerPos, SelectedText},
     impliment_content,
+    register::{ContentRegister, Id},
+    service::{config::config, theme::theme},
     traits::Display,
 };
 
-use super::traits::ContentTrait;
+use super::traits::{Content, ContentTrait};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Todo {
@@ -23,15 +25,85 @@ pub struct Todo {
     pub due_date: Option<Date>,
     pub due_time: Option<Time>,
     pub status: TodoStatus,
+    #[serde(default)]
+    pub subtasks: Option<Id>, // id of a TodoList
+    #[serde(default)]
+    pub uid: Option<String>, // stable id for syncing with other apps (ical UID)
+    #[serde(default)]
+    pub created_at: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub status_changed_at: Option<DateTime<Local>>,
+    /// tracked time. only the last one can still be running
+    #[serde(default)]
+    pub intervals: Vec<Interval>,
+    /// when each finished pomodoro work phase ended
+    #[serde(default)]
+    pub pomodoros: Vec<DateTime<Local>>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub priority: Option<Priority>,
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Interval {
+    pub start: DateTime<Local>,
+    /// None while the timer runs
+    pub end: Option<DateTime<Local>>,
+}
+impl Interval {
+    pub fn duration(&self) -> Duration {
+        self.end.unwrap_or_else(Local::now) - self.start
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub enum TodoStatus {
     Pending,
     Done,
     Ignored,
 }
 
+/// ordered from low to high
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+}
+impl Priority {
+    /// low, medium (or med) or high
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "low" => Some(Self::Low),
+            "medium" | "med" => Some(Self::Medium),
+            "high" => Some(Self::High),
+            _ => None,
+        }
+    }
+
+    /// !, !! or !!!
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Low => "!",
+            Self::Medium => "!!",
+            Self::High => "!!!",
+        }
+    }
+
+    pub fn style(self) -> Style {
+        let theme = theme();
+        match self {
+            Self::Low => theme.text,
+            Self::Medium => theme.today,
+            Self::High => theme.overdue,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Deserialize, Serialize)]
 pub struct Date {
     pub day: u8,
@@ -67,8 +139,186 @@ impl Todo {
             due_date: None,
             due_time: None,
             status: TodoStatus::Pending,
+            subtasks: None,
+            uid: None,
+            created_at: Some(Local::now()),
+            completed_at: None,
+            status_changed_at: None,
+            intervals: vec![],
+            pomodoros: vec![],
+            tags: vec![],
+            priority: None,
+        }
+    }
+
+    /// how close a pending todo is to being due
+    pub fn urgency(&self, now: NaiveDateTime) -> Option<Urgency> {
+        if self.status != TodoStatus::Pending {
+            return None;
+        }
+        let due = self.due()?;
+        let days = (due.date() - now.date()).num_days();
+        let config = &config().due;
+        Some(match days {
+            _ if due < now => Urgency::Overdue,
+            0 => Urgency::Today,
+            d if d <= config.soon_days.into() => Urgency::Soon,
+            d if d > config.far_days.into() => Urgency::Far,
+            _ => Urgency::Later,
+        })
+    }
+
+    /// the due date as configured, relative or dd-mm-yyyy
+    pub fn due_text(&self, today: NaiveDate) -> Option<String> {
+        let date = NaiveDate::from(self.due_date?);
+        Some(match config().due.relative {
+            true => relative_date(date, today),
+            false => date.format("%d-%m-%Y").to_string(),
+        })
+    }
+
+    /// the moment the todo is due. todos without a time (or with one that does not exist) are due at the end
+    /// of the day
+    pub fn due(&self) -> Option<NaiveDateTime> {
+        let date = NaiveDate::from(self.due_date?);
+        let time = self
+            .due_time
+            .as_ref()
+            .and_then(|t| NaiveTime::from_hms_opt(t.hour.into(), t.min.into(), t.sec.into()))
+            .unwrap_or(NaiveTime::from_hms_opt(23, 59, 59).unwrap());
+        Some(date.and_time(time))
+    }
+
+    pub fn is_tracking(&self) -> bool {
+        self.intervals.last().is_some_and(|i| i.end.is_none())
+    }
+
+    pub fn start_timer(&mut self) {
+        if !self.is_tracking() {
+            self.intervals.push(Interval {
+                start: Local::now(),
+                end: None,
+            });
+        }
+    }
+
+    pub fn stop_timer(&mut self) {
+        if let Some(i) = self.intervals.last_mut().filter(|i| i.end.is_none()) {
+            i.end = Some(Local::now());
         }
     }
+
+    /// total tracked time, including the running timer
+    pub fn tracked(&self) -> Duration {
+        self.intervals
+            .iter()
+            .fold(Duration::zero(), |total, i| total + i.duration())
+    }
+
+    /// changes the status and records when it happened
+    pub fn set_status(&mut self, status: TodoStatus) {
+        if self.status == status {
+            return;
+        }
+        let now = Local::now();
+        self.status = status;
+        self.status_changed_at = Some(now);
+        self.completed_at = match status {
+            TodoStatus::Done => Some(now),
+            _ => None,
+        };
+        if status == TodoStatus::Done {
+            self.stop_timer();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Overdue,
+    Today,
+    /// within soon_days
+    Soon,
+    Later,
+    /// after far_days
+    Far,
+}
+
+impl Urgency {
+    pub fn style(self) -> Style {
+        let theme = theme();
+        match self {
+            Self::Overdue => theme.overdue,
+            Self::Today => theme.today,
+            Self::Soon => theme.soon,
+            Self::Later => theme.text,
+            Self::Far => theme.dim,
+        }
+    }
+}
+
+/// the todo whose timer is running. there is at most one
+pub fn running_timer(register: &ContentRegister<Content, Id>) -> Option<(Id, &Todo)> {
+    register.iter().find_map(|(id, c)| {
+        c.as_any()
+            .downcast_ref::<Todo>()
+            .filter(|t| t.is_tracking())
+            .map(|t| (id, t))
+    })
+}
+
+/// dd-mm-yyyy, today, tomorrow, yesterday, a weekday (the next one) or +n for in n days
+pub fn parse_date(text: &str) -> Option<NaiveDate> {
+    let today = Local::now().naive_local().date();
+    let text = text.trim().to_lowercase();
+    match text.as_str() {
+        "today" => return Some(today),
+        "tomorrow" => return Some(today.succ()),
+        "yesterday" => return Some(today.pred()),
+        _ => (),
+    }
+    if let Some(n) = text.strip_prefix('+') {
+        return n.parse().ok().map(|n| today + Duration::days(n));
+    }
+    if let Ok(day) = text.parse::<Weekday>() {
+        let ahead =
+            (day.num_days_from_monday() + 6 - today.weekday().num_days_from_monday()) % 7 + 1;
+        return Some(today + Duration::days(ahead.into()));
+    }
+    NaiveDate::parse_from_str(&text, "%d-%m-%Y").ok()
+}
+
+/// today, tomorrow, yesterday, in 3d, 2w ago, in 4mo or 1y ago
+pub fn relative_date(date: NaiveDate, today: NaiveDate) -> String {
+    let days = (date - today).num_days();
+    let span = match days.abs() {
+        0 => return "today".to_owned(),
+        1 if days > 0 => return "tomorrow".to_owned(),
+        1 => return "yesterday".to_owned(),
+        n if n < 14 => format!("{n}d"),
+        n if n < 60 => format!("{}w", n / 7),
+        n if n < 365 => format!("{}mo", n / 30),
+        n => format!("{}y", n / 365),
+    };
+    match days > 0 {
+        true => format!("in {span}"),
+        false => format!("{span} ago"),
+    }
+}
+
+/// 2d 3h, 1h 20m or 5m
+pub fn format_duration(d: Duration) -> String {
+    match (d.num_days(), d.num_hours() % 24, d.num_minutes() % 60) {
+        (0, 0, m) => format!("{m}m"),
+        (0, h, m) => format!("{h}h {m}m"),
+        (days, h, _) => format!("{days}d {h}h"),
+    }
+}
+
+/// 01:02:03
+pub fn format_clock(d: Duration) -> String {
+    let s = d.num_seconds().max(0);
+    format!("{:02}:{:02}:{:02}", s / 3600, s / 60 % 60, s % 60)
 }
 
 impl Display for Todo {
@@ -78,29 +328,147 @@ impl Display for Todo {
         self.content.clone()
     }
     fn display(&self) -> Self::Output {
+        self.item(true, true)
+    }
+    fn set_text(&mut self, name: Cow<'static, str>) {
+        self.content = name;
+    }
+}
+
+impl Todo {
+    /// the tracked time and finished pomodoros, if there are any
+    pub fn tracked_text(&self) -> Option<String> {
+        let tracked = self.tracked();
+        let mut extra = vec![];
+        if tracked > Duration::zero() {
+            extra.push(format_duration(tracked));
+        }
+        match self.pomodoros.len() {
+            0 => (),
+            1 => extra.push("1 pomodoro".to_owned()),
+            n => extra.push(format!("{n} pomodoros")),
+        }
+        Some(extra.join(" · ")).filter(|e| !e.is_empty())
+    }
+
+    /// #a #b
+    pub fn tags_text(&self) -> String {
+        self.tags
+            .iter()
+            .map(|t| format!("#{t}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// the todo as an item, with its tags and tracked time after the text unless they have a column of their own
+    pub fn item(&self, tags: bool, tracked: bool) -> Item<'static> {
         let mut text = Line::new(Span::raw(self.text()));
         let mut selected_text = text.clone();
         let st = match self.status {
-            TodoStatus::Pending => Style::default().fg(Color::Rgb(200, 200, 100)),
-            TodoStatus::Done => Style::default()
-                .fg(Color::Rgb(90, 130, 90))
-                .add_modifier(Modifier::CROSSED_OUT),
-            TodoStatus::Ignored => Style::default().fg(Color::DarkGray),
+            TodoStatus::Pending => theme().pending,
+            TodoStatus::Done => theme().done,
+            TodoStatus::Ignored => theme().ignored,
         };
         text.text_style(st);
-        selected_text.text_style(st.add_modifier(Modifier::BOLD));
+        selected_text.text_style(st.patch(theme().selected));
+        if tags && !self.tags.is_empty() {
+            let span = Span::styled(format!("  {}", self.tags_text()), theme().tag);
+            text.main_text.0.push(span.clone());
+            selected_text.main_text.0.push(span);
+        }
+        if let Some(extra) = self.tracked_text().filter(|_| tracked) {
+            let span = Span::styled(format!("  {extra}"), theme().dim);
+            text.main_text.0.push(span.clone());
+            selected_text.main_text.0.push(span);
+        }
+        if self.is_tracking() {
+            let marker = Marker {
+                symbol: Span::styled("●", theme().running),
+                pos: MarkerPos::Left,
+            };
+            text.markers.push(marker.clone());
+            selected_text.markers.push(marker);
+        }
+        if self.urgency(Local::now().naive_local()) == Some(Urgency::Overdue) {
+            let marker = Marker {
+                symbol: Span::styled("!", theme().overdue),
+                pos: MarkerPos::Left,
+            };
+            text.markers.push(marker.clone());
+            selected_text.markers.push(marker);
+        }
+        if self.subtasks.is_some() {
+            let marker = Marker {
+                symbol: Span::styled("▸", st),
+                pos: MarkerPos::Left,
+            };
+            text.markers.push(marker.clone());
+            selected_text.markers.push(marker);
+        }
 
         Item {
             text: vec![text.clone()],
             selected_text: SelectedText::Lines(vec![selected_text]),
         }
     }
-    fn set_text(&mut self, name: Cow<'static, str>) {
-        self.content = name;
-    }
 }
 
 #[typetag::serde]
 impl ContentTrait for Todo {
     impliment_content!(Todo, Display, IDK);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relative(days: i64) -> String {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        relative_date(today + Duration::days(days), today)
+    }
+
+    #[test]
+    fn relative_date_names_the_next_days() {
+        assert_eq!(relative(0), "today");
+        assert_eq!(relative(1), "tomorrow");
+        assert_eq!(relative(-1), "yesterday");
+    }
+
+    #[test]
+    fn relative_date_counts_days_weeks_months_and_years() {
+        assert_eq!(relative(2), "in 2d");
+        assert_eq!(relative(13), "in 13d");
+        assert_eq!(relative(14), "in 2w");
+        assert_eq!(relative(59), "in 8w");
+        assert_eq!(relative(60), "in 2mo");
+        assert_eq!(relative(364), "in 12mo");
+        assert_eq!(relative(365), "in 1y");
+        assert_eq!(relative(-3), "3d ago");
+        assert_eq!(relative(-21), "3w ago");
+        assert_eq!(relative(-800), "2y ago");
+    }
+
+    #[test]
+    fn due_falls_back_to_the_end_of_the_day() {
+        let mut t = Todo::new("a");
+        t.due_date = Some(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().into());
+        let at = |h, m, s| {
+            NaiveDate::from_ymd_opt(2024, 3, 1)
+                .unwrap()
+                .and_hms_opt(h, m, s)
+        };
+        assert_eq!(t.due(), at(23, 59, 59));
+        t.due_time = Some(Time {
+            hour: 9,
+            min: 30,
+            sec: 0,
+        });
+        assert_eq!(t.due(), at(9, 30, 0));
+        t.due_time = Some(Time {
+            hour: 25,
+            min: 0,
+            sec: 0,
+        });
+        assert_eq!(t.due(), at(23, 59, 59));
+    }
+}
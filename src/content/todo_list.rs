@@ -1,8 +1,8 @@
 #[allow(unused_imports)]
 use crate::{dbg, debug, error};
 
-use chrono::{Datelike, NaiveDate};
-use crossterm::event::{Event, KeyCode};
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use crossterm::event::{Event, KeyCode, KeyModifiers};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use tui::{
@@ -13,22 +13,32 @@ use tui::{
 };
 
 use crate::{
+    alt,
     app::{self, AppAction, AppActionCallback},
-    content::todo::{Date, Todo, TodoStatus},
+    content::{
+        columns::{Column, ListColumn},
+        todo::{format_duration, parse_date, running_timer, Date, Priority, Todo, TodoStatus},
+    },
     ctrl,
-    display::{Item, Line, ListBuilder, SelectedText},
+    display::{Item, Line, ListBuilder, Marker, MarkerPos, SelectedText},
     key,
-    register::Id,
+    register::{ContentRegister, Id},
     service::{
-        editors::{Edit, Yank},
+        editors::{Edit, Yank, Yanker},
         insert_mode::{InsertAction, InsertMode},
+        theme::theme,
+        tree,
     },
     shift,
-    traits::{Display, EventAction, EventHandler, Provider, SelectedIndex, Widget, YankDest},
+    traits::{
+        key_press, CommandInfo, Commands, Display, EventAction, EventHandler, Mode, Provider,
+        SelectedIndex, Status, Widget, YankDest,
+    },
 };
 
 use super::traits::{
-    impliment_content, Container, Content, ContentTrait, DisplayContext, WidgetOutput,
+    impliment_content, matched_chars, Container, Content, ContentTrait, DisplayContext, Jump,
+    ListSearch, Table, WidgetOutput,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -37,6 +47,8 @@ enum ListenTarget {
     ContentEdit,
     DueDate,
     DueTime,
+    /// typing a / search
+    Search,
     None,
 }
 impl Default for ListenTarget {
@@ -49,6 +61,8 @@ impl Default for ListenTarget {
 pub struct TodoList {
     container: Container<Id>,
     title: Cow<'static, str>,
+    #[serde(default = "ListColumn::defaults")]
+    columns: Vec<ListColumn>,
     #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
     insert_mode: InsertMode,
     #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
@@ -60,10 +74,349 @@ impl TodoList {
         Self {
             container: Default::default(),
             title: title.into(),
+            columns: ListColumn::defaults(),
             insert_mode: Default::default(),
             listen_target: Default::default(),
         }
     }
+
+    pub fn items(&self) -> &[Id] {
+        &self.container.items
+    }
+}
+
+/// time tracked on the todos of the list, subtasks included
+pub fn tracked_in_list(register: &ContentRegister<Content, Id>, list: Id) -> Duration {
+    match register
+        .get(list)
+        .and_then(|c| c.as_any().downcast_ref::<TodoList>())
+    {
+        Some(list) => tracked_in_items(register, list.items()),
+        None => Duration::zero(),
+    }
+}
+
+fn tracked_in_items(register: &ContentRegister<Content, Id>, items: &[Id]) -> Duration {
+    items
+        .iter()
+        .filter_map(|&id| register.get(id))
+        .filter_map(|c| c.as_any().downcast_ref::<Todo>())
+        .fold(Duration::zero(), |total, t| {
+            total
+                + t.tracked()
+                + t.subtasks
+                    .map_or(Duration::zero(), |sub| tracked_in_list(register, sub))
+        })
+}
+
+/// moves the selection of the list to a match of its search
+fn jump(self_id: Id, jump: Jump) -> AppAction {
+    AppAction::Callback {
+        call: Box::new(move |ctx| {
+            let me = ctx
+                .register
+                .get(self_id)
+                .unwrap()
+                .as_any()
+                .downcast_ref::<TodoList>()
+                .unwrap();
+            let texts = me
+                .items()
+                .iter()
+                .map(|&id| {
+                    ctx.register
+                        .get(id)
+                        .map(|c| c.as_display().text().into_owned())
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>();
+            let me = ctx
+                .register
+                .get_mut(self_id)
+                .unwrap()
+                .as_any_mut()
+                .downcast_mut::<TodoList>()
+                .unwrap();
+            if me.container.jump_to_match(&texts, jump) {
+                return Ok(AppAction::None);
+            }
+            Ok(match &me.container.search {
+                Some(s) if s.query.is_empty() => AppAction::None,
+                Some(s) if s.matcher().is_none() => {
+                    AppAction::error(format!("invalid regex: {}", s.query))
+                }
+                Some(s) => AppAction::error(format!("not found: {}", s.query)),
+                None => AppAction::None,
+            })
+        }),
+    }
+}
+
+impl TodoList {
+    /// events while typing a / search. the selection follows the first match
+    fn search_events(&mut self, event: &Event, self_id: Id) -> EventAction<AppAction> {
+        let search = match self.container.search.as_mut() {
+            Some(s) => s,
+            None => {
+                self.listen_target = ListenTarget::None;
+                return EventAction::Unabsorbed(AppAction::None);
+            }
+        };
+        match event {
+            Event::Key(alt!('r')) => search.regex = !search.regex,
+            Event::Key(alt!('c')) => search.ignore_case = !search.ignore_case,
+            _ => match self.insert_mode.handle_events(event, ()) {
+                InsertAction::Action(EventAction::Unabsorbed(a)) => {
+                    return EventAction::Unabsorbed(a)
+                }
+                InsertAction::Action(EventAction::Absorbed(_)) => {
+                    search.query = self.insert_mode.text();
+                }
+                InsertAction::Accepted { text, .. } => {
+                    self.listen_target = ListenTarget::None;
+                    if text.is_empty() {
+                        self.container.search = None;
+                    }
+                    return EventAction::Absorbed(AppAction::None);
+                }
+                InsertAction::Rejected(_) => {
+                    self.listen_target = ListenTarget::None;
+                    let origin = search.origin;
+                    self.container.search = None;
+                    if origin < self.container.items.len() {
+                        self.container.selected_index.select(origin);
+                    }
+                    return EventAction::Absorbed(AppAction::None);
+                }
+            },
+        }
+        EventAction::Absorbed(jump(self_id, Jump::First))
+    }
+}
+
+/// 1 todo, 3 todos
+fn todos(n: usize) -> String {
+    match n {
+        1 => "1 todo".to_owned(),
+        n => format!("{n} todos"),
+    }
+}
+
+fn set_priority(targets: Vec<Id>, priority: Option<Priority>) -> AppAction {
+    AppAction::Callback {
+        call: Box::new(move |ctx| {
+            for &id in &targets {
+                if let Some(t) = ctx
+                    .register
+                    .get_mut(id)
+                    .and_then(|c| c.as_any_mut().downcast_mut::<Todo>())
+                {
+                    t.priority = priority;
+                }
+            }
+            Ok(AppAction::None)
+        }),
+    }
+}
+
+fn set_due(targets: Vec<Id>, date: Option<Date>) -> AppAction {
+    AppAction::Callback {
+        call: Box::new(move |ctx| {
+            for &id in &targets {
+                if let Some(t) = ctx
+                    .register
+                    .get_mut(id)
+                    .and_then(|c| c.as_any_mut().downcast_mut::<Todo>())
+                {
+                    t.due_date = date;
+                }
+            }
+            Ok(AppAction::None)
+        }),
+    }
+}
+
+/// removes the todo and its subtasks from the register, all the way down
+fn delete_todo(register: &mut ContentRegister<Content, Id>, id: Id) {
+    let subtasks = register
+        .get(id)
+        .and_then(|c| c.as_any().downcast_ref::<Todo>())
+        .and_then(|t| t.subtasks);
+    if let Some(list) = subtasks {
+        let items = register
+            .get(list)
+            .and_then(|c| c.as_any().downcast_ref::<TodoList>())
+            .map(|l| l.items().to_vec())
+            .unwrap_or_default();
+        for id in items {
+            delete_todo(register, id);
+        }
+        register.remove(list);
+    }
+    register.remove(id);
+}
+
+fn delete(self_id: Id, targets: Vec<Id>) -> AppAction {
+    AppAction::Callback {
+        call: Box::new(move |ctx| {
+            ctx.register
+                .get_mut(self_id)
+                .unwrap()
+                .as_any_mut()
+                .downcast_mut::<TodoList>()
+                .unwrap()
+                .container
+                .remove_all(&targets);
+            for &id in &targets {
+                delete_todo(ctx.register, id);
+            }
+            Ok(AppAction::info(format!("deleted {}", todos(targets.len()))))
+        }),
+    }
+}
+
+/// a copy of the todo with copies of its subtasks. the copy starts without tracked time
+fn copy_todo(register: &mut ContentRegister<Content, Id>, id: Id) -> Option<Id> {
+    let mut todo = register.get(id)?.as_any().downcast_ref::<Todo>()?.clone();
+    todo.uid = None;
+    todo.created_at = Some(Local::now());
+    todo.intervals.clear();
+    todo.pomodoros.clear();
+    if let Some(list) = todo.subtasks {
+        let mut copy = register
+            .get(list)?
+            .as_any()
+            .downcast_ref::<TodoList>()?
+            .clone();
+        copy.container = Container {
+            items: copy
+                .items()
+                .iter()
+                .filter_map(|&id| copy_todo(register, id))
+                .collect(),
+            ..Default::default()
+        };
+        todo.subtasks = Some(register.alloc(Content::from(copy)));
+    }
+    Some(register.alloc(Content::from(todo)))
+}
+
+/// copies of the yanked todos below the selection
+fn paste(self_id: Id) -> AppAction {
+    AppAction::Callback {
+        call: Box::new(move |ctx| {
+            let yanks = match &ctx.editor.yanker {
+                Some(y) => y.yanks.iter().map(|y| y.id).collect::<Vec<_>>(),
+                None => return Ok(AppAction::error("nothing yanked")),
+            };
+            let copies = yanks
+                .into_iter()
+                .filter_map(|id| copy_todo(ctx.register, id))
+                .collect::<Vec<_>>();
+            let me = ctx
+                .register
+                .get_mut(self_id)
+                .unwrap()
+                .as_any_mut()
+                .downcast_mut::<TodoList>()
+                .unwrap();
+            let pos = me
+                .items()
+                .len()
+                .min(me.container.selected_index.selected_index() + 1);
+            me.container.items.splice(pos..pos, copies.iter().copied());
+            Ok(AppAction::info(format!("pasted {}", todos(copies.len()))))
+        }),
+    }
+}
+
+/// whether the list is the subtasks of the todo or of one of its subtasks
+fn is_under(register: &ContentRegister<Content, Id>, todo: Id, list: Id) -> bool {
+    let subtasks = match register
+        .get(todo)
+        .and_then(|c| c.as_any().downcast_ref::<Todo>())
+        .and_then(|t| t.subtasks)
+    {
+        Some(s) => s,
+        None => return false,
+    };
+    subtasks == list
+        || register
+            .get(subtasks)
+            .and_then(|c| c.as_any().downcast_ref::<TodoList>())
+            .is_some_and(|l| l.items().iter().any(|&id| is_under(register, id, list)))
+}
+
+/// moves the todos to the end of the list with the title. only lists shown from the main provider count,
+/// not subtask lists
+fn move_to(self_id: Id, targets: Vec<Id>, title: String) -> AppAction {
+    AppAction::Callback {
+        call: Box::new(move |ctx| {
+            let mut lists = tree::lists_called(ctx.register, ctx.stack.main_provider(), &title);
+            lists.retain(|&(id, _)| id != self_id);
+            let (dest, title) = match lists.as_slice() {
+                [] => return Ok(AppAction::error(format!("no list called {title}"))),
+                [dest] => dest.clone(),
+                _ => {
+                    return Ok(AppAction::error(format!(
+                        "{} lists are called {title}",
+                        lists.len()
+                    )))
+                }
+            };
+            if targets.iter().any(|&id| is_under(ctx.register, id, dest)) {
+                return Ok(AppAction::error("can't move a todo into its own subtasks"));
+            }
+            ctx.register
+                .get_mut(self_id)
+                .unwrap()
+                .as_any_mut()
+                .downcast_mut::<TodoList>()
+                .unwrap()
+                .container
+                .remove_all(&targets);
+            ctx.register
+                .get_mut(dest)
+                .unwrap()
+                .as_any_mut()
+                .downcast_mut::<TodoList>()
+                .unwrap()
+                .container
+                .items
+                .extend(&targets);
+            Ok(AppAction::info(format!(
+                "moved {} to {title}",
+                todos(targets.len())
+            )))
+        }),
+    }
+}
+
+/// adds or removes the tag on the todos
+fn tag(targets: Vec<Id>, tag: String, add: bool) -> AppAction {
+    AppAction::Callback {
+        call: Box::new(move |ctx| {
+            for &id in &targets {
+                if let Some(t) = ctx
+                    .register
+                    .get_mut(id)
+                    .and_then(|c| c.as_any_mut().downcast_mut::<Todo>())
+                {
+                    t.tags.retain(|t| t != &tag);
+                    if add {
+                        t.tags.push(tag.clone());
+                    }
+                }
+            }
+            Ok(AppAction::None)
+        }),
+    }
+}
+
+fn not_a_date(text: &str) -> AppAction {
+    AppAction::error(format!(
+        "not a date: {text}, expected dd-mm-yyyy, today, tomorrow, a weekday or +n"
+    ))
 }
 
 impl<'a> EventHandler<'a> for TodoList {
@@ -83,11 +436,14 @@ impl<'a> EventHandler<'a> for TodoList {
                     id,
                     pos: me.container.selected_index.selected_index(),
                 });
-                ctx.register.unregister(id);
+                ctx.register.remove(id);
                 Ok(AppAction::None)
             }),
         };
 
+        if let ListenTarget::Search = self.listen_target {
+            return self.search_events(event, self_id);
+        }
         let a = match self.insert_mode.handle_events(event, ()) {
             InsertAction::Action(a) => a,
             InsertAction::Accepted { action, text } => {
@@ -96,6 +452,14 @@ impl<'a> EventHandler<'a> for TodoList {
                     .trim_start_matches(' ')
                     .to_owned();
                 let id = self.container.items[self.container.selected_index.selected_index()];
+                let targets = match self.listen_target {
+                    ListenTarget::DueDate => {
+                        let targets = self.container.selection();
+                        self.container.clear_marks();
+                        targets
+                    }
+                    _ => vec![id],
+                };
                 let a = if text.len() > 0 {
                     let a = match self.listen_target {
                         ListenTarget::ContentCreate | ListenTarget::ContentEdit => {
@@ -110,47 +474,21 @@ impl<'a> EventHandler<'a> for TodoList {
                                 }),
                             }])
                         }
-                        ListenTarget::DueDate => {
-                            action.chain([AppAction::Callback {
-                                call: Box::new(move |ctx| {
-                                    let t = ctx
-                                        .register
-                                        .get_mut(id)
-                                        .unwrap()
-                                        .as_any_mut()
-                                        .downcast_mut::<Todo>()
-                                        .unwrap();
-                                    // let today = chrono::offset::Local::now();//.naive_local();
-                                    t.due_date = NaiveDate::parse_from_str(&text, "%d-%m-%Y")
-                                        .ok()
-                                        .map(Date::from)
-                                        .or(t.due_date);
-                                    Ok(AppAction::None)
-                                }),
-                            }])
-                        }
+                        ListenTarget::DueDate => match parse_date(&text) {
+                            Some(date) => action.chain([set_due(targets, Some(Date::from(date)))]),
+                            None => action.chain([not_a_date(&text)]),
+                        },
                         ListenTarget::DueTime => todo!(),
-                        ListenTarget::None => unreachable!(),
+                        ListenTarget::Search | ListenTarget::None => unreachable!(),
                     };
                     a
                 } else {
                     match self.listen_target {
                         ListenTarget::ContentCreate => action.chain([rejected_creation(id)]),
-                        ListenTarget::DueDate => action.chain([AppAction::Callback {
-                            call: Box::new(move |ctx| {
-                                ctx.register
-                                    .get_mut(id)
-                                    .unwrap()
-                                    .as_any_mut()
-                                    .downcast_mut::<Todo>()
-                                    .unwrap()
-                                    .due_date = None;
-                                Ok(AppAction::None)
-                            }),
-                        }]),
+                        ListenTarget::DueDate => action.chain([set_due(targets, None)]),
                         ListenTarget::DueTime => todo!(),
                         ListenTarget::ContentEdit => action,
-                        ListenTarget::None => unreachable!(),
+                        ListenTarget::Search | ListenTarget::None => unreachable!(),
                     }
                 };
                 self.listen_target = ListenTarget::None;
@@ -240,14 +578,68 @@ impl<'a> EventHandler<'a> for TodoList {
                     };
                     return EventAction::Absorbed(a.chain([action]));
                 }
+                key!('/') => {
+                    self.container.search = Some(ListSearch::new(
+                        self.container.selected_index.selected_index(),
+                    ));
+                    self.listen_target = ListenTarget::Search;
+                    self.insert_mode.listen();
+                    return EventAction::Absorbed(a);
+                }
+                key!('n') if self.container.search.is_some() => {
+                    return EventAction::Absorbed(a.chain([jump(self_id, Jump::Next)]));
+                }
+                shift!('N') if self.container.search.is_some() => {
+                    return EventAction::Absorbed(a.chain([jump(self_id, Jump::Prev)]));
+                }
+                key!(Esc) if self.container.has_marks() => {
+                    self.container.clear_marks();
+                    return EventAction::Absorbed(a);
+                }
+                key!(Esc) if self.container.search.is_some() => {
+                    self.container.search = None;
+                    return EventAction::Absorbed(a);
+                }
                 shift!('D') => {
                     self.insert_mode.listen();
                     self.listen_target = ListenTarget::DueDate;
                     return EventAction::Absorbed(a);
                 }
-                // key!('t') => {
-                //     todo!()
-                // }
+                key!('t') => {
+                    let id = match self.get_selected() {
+                        Some(id) => id,
+                        None => return EventAction::Absorbed(a),
+                    };
+                    let action = AppAction::Callback {
+                        call: Box::new(move |ctx| {
+                            // only one timer runs at a time
+                            let running = running_timer(ctx.register).map(|(id, _)| id);
+                            if let Some(running) = running {
+                                ctx.register
+                                    .get_mut(running)
+                                    .unwrap()
+                                    .as_any_mut()
+                                    .downcast_mut::<Todo>()
+                                    .unwrap()
+                                    .stop_timer();
+                            }
+                            if running != Some(id) {
+                                if let Some(t) = ctx
+                                    .register
+                                    .get_mut(id)
+                                    .unwrap()
+                                    .as_any_mut()
+                                    .downcast_mut::<Todo>()
+                                    .filter(|t| t.status == TodoStatus::Pending)
+                                {
+                                    t.start_timer();
+                                }
+                            }
+                            Ok(AppAction::None)
+                        }),
+                    };
+                    return EventAction::Absorbed(a.chain([action]));
+                }
                 // shift!('T') => {
                 //     self.insert_mode.listen();
                 //     self.listen_target = ListenTarget::DueTime;
@@ -274,25 +666,28 @@ impl<'a> EventHandler<'a> for TodoList {
                     return EventAction::Absorbed(a.chain([action]));
                 }
                 key!('c') => {
-                    let id = self.container.items[self.container.selected_index.selected_index()];
+                    let targets = self.container.selection();
+                    self.container.clear_marks();
                     let action = AppAction::Callback {
                         call: Box::new(move |ctx| {
-                            let t = ctx
-                                .register
-                                .get_mut(id)
-                                .unwrap()
-                                .as_any_mut()
-                                .downcast_mut::<Todo>()
-                                .unwrap();
-                            match t.status {
-                                TodoStatus::Pending => {
-                                    t.status = TodoStatus::Done;
-                                }
-                                TodoStatus::Done => {
-                                    t.status = TodoStatus::Pending;
-                                }
-                                TodoStatus::Ignored => {
-                                    t.status = TodoStatus::Done;
+                            // all done if any is not, otherwise all back to pending
+                            let all_done = targets.iter().all(|&id| {
+                                ctx.register
+                                    .get(id)
+                                    .and_then(|c| c.as_any().downcast_ref::<Todo>())
+                                    .is_some_and(|t| t.status == TodoStatus::Done)
+                            });
+                            let status = match all_done {
+                                true => TodoStatus::Pending,
+                                false => TodoStatus::Done,
+                            };
+                            for &id in &targets {
+                                if let Some(t) = ctx
+                                    .register
+                                    .get_mut(id)
+                                    .and_then(|c| c.as_any_mut().downcast_mut::<Todo>())
+                                {
+                                    t.set_status(status);
                                 }
                             }
                             Ok(AppAction::None)
@@ -300,6 +695,48 @@ impl<'a> EventHandler<'a> for TodoList {
                     };
                     return EventAction::Absorbed(a.chain([action]));
                 }
+                key!(' ') => {
+                    self.container.toggle_mark();
+                    let last = self.container.items.len().saturating_sub(1);
+                    self.container
+                        .selected_index
+                        .select(last.min(self.container.selected_index.selected_index() + 1));
+                    return EventAction::Absorbed(a);
+                }
+                shift!('V') => {
+                    self.container.toggle_visual();
+                    return EventAction::Absorbed(a);
+                }
+                key!('x') => {
+                    let targets = self.container.selection();
+                    self.container.clear_marks();
+                    return EventAction::Absorbed(a.chain([delete(self_id, targets)]));
+                }
+                key!('y') => {
+                    let targets = self.container.selection();
+                    self.container.clear_marks();
+                    let yanks = targets
+                        .into_iter()
+                        .map(|id| Yank {
+                            id,
+                            pos: self.items().iter().position(|&i| i == id).unwrap(),
+                        })
+                        .collect::<Vec<_>>();
+                    let action = AppAction::Callback {
+                        call: Box::new(move |ctx| {
+                            let n = yanks.len();
+                            ctx.editor.yanker = Some(Yanker {
+                                yanks,
+                                source: self_id,
+                            });
+                            Ok(AppAction::info(format!("yanked {}", todos(n))))
+                        }),
+                    };
+                    return EventAction::Absorbed(a.chain([action]));
+                }
+                ctrl!('v') => {
+                    return EventAction::Absorbed(a.chain([paste(self_id)]));
+                }
 
                 // TODO: temporary implimentation. till yanking is properly implimented
                 ctrl!('j') => {
@@ -338,88 +775,129 @@ impl<'a> Widget<'a> for TodoList {
     type Context = DisplayContext<'a>;
     type Output = WidgetOutput<'static>; // ? maybe try use GAT here
     fn display(&self, context: Self::Context) -> Self::Output {
-        let mut content = ListBuilder::default();
-        content
-            .title(Span::raw(format!("List Name: {}", self.title.clone())))
-            .block(
-                Block::default()
-                    .border_style(Style::default().fg(Color::Rgb(150, 150, 150)))
-                    // .borders(Borders::TOP | Borders::LEFT | Borders::BOTTOM)
-                    .borders(Borders::all()),
-            );
-        content.items = self
-            .container
-            .items
-            .iter()
-            .cloned()
-            .map(|id| {
-                context
-                    .content_register
-                    .get(id)
-                    .map(|e| e.as_display().display())
-                    .unwrap()
-            })
-            .collect();
+        let register = context.content_register;
+        let shown = |column| self.columns.iter().any(|c| c.column == column);
+        let now = Local::now().naive_local();
+        let mut table = Table::default();
+        for c in &self.columns {
+            let list = match c.column {
+                Column::Title => {
+                    self.title_list(register, !shown(Column::Tags), !shown(Column::Tracked))
+                }
+                column => column.list(self.items(), register, now),
+            };
+            table.columns.push((list, c.width));
+        }
+        if let Some((list, _)) = table.columns.first_mut() {
+            list.set_numbered(true);
+        }
 
-        let mut date = ListBuilder::default();
-        date.block(
+        if self.insert_mode.is_listening() {
+            let line = self.insert_mode.line();
+            let item = Item {
+                text: vec![line.clone()],
+                selected_text: SelectedText::Lines(vec![line]),
+            };
+            let column = |column| self.columns.iter().position(|c| c.column == column);
+            let title = column(Column::Title).unwrap_or(0);
+            let index = match self.listen_target {
+                ListenTarget::ContentCreate | ListenTarget::ContentEdit => Some(title),
+                // typed over the title when there is no due column
+                ListenTarget::DueDate => Some(column(Column::Due).unwrap_or(title)),
+                ListenTarget::DueTime => todo!(),
+                // shown in the title
+                ListenTarget::Search => None,
+                ListenTarget::None => unreachable!(),
+            };
+            if let Some((list, _)) = index.and_then(|i| table.columns.get_mut(i)) {
+                list.items[self.container.selected_index.selected_index()] = item;
+            }
+        }
+        WidgetOutput::Table(table)
+    }
+}
+
+impl TodoList {
+    /// the todos with the list title, marks and search. tags and tracked time go after the text
+    /// unless they have columns of their own
+    fn title_list(
+        &self,
+        register: &ContentRegister<Content, Id>,
+        tags: bool,
+        tracked: bool,
+    ) -> ListBuilder<'static> {
+        let mut content = ListBuilder::default();
+        let tracked_time = tracked_in_items(register, self.items());
+        let title = match tracked_time > Duration::zero() {
+            true => format!(
+                "List Name: {} ({})",
+                self.title,
+                format_duration(tracked_time)
+            ),
+            false => format!("List Name: {}", self.title),
+        };
+        let mut title = vec![Span::raw(title)];
+        let marked = (0..self.items().len())
+            .filter(|&i| self.container.is_marked(i))
+            .count();
+        if marked > 0 {
+            title.push(Span::styled(format!("  {marked} marked"), theme().marked));
+        }
+        if let Some(search) = &self.container.search {
+            let st = theme().matched;
+            title.push(Span::styled("  /", st));
+            match self.listen_target {
+                ListenTarget::Search => title.extend(self.insert_mode.line().main_text.0),
+                _ => title.push(Span::styled(search.query.clone(), st)),
+            }
+            let flags = [(search.regex, " [regex]"), (!search.ignore_case, " [case]")];
+            for (_, flag) in flags.into_iter().filter(|(on, _)| *on) {
+                title.push(Span::styled(flag, theme().dim));
+            }
+        }
+        content.title(Spans::from(title)).block(
             Block::default()
-                .title("Due Date")
-                .border_style(Style::default().fg(Color::Rgb(150, 150, 150)))
-                // .borders(Borders::TOP | Borders::BOTTOM | Borders::RIGHT)
+                .border_style(theme().border)
+                // .borders(Borders::TOP | Borders::LEFT | Borders::BOTTOM)
                 .borders(Borders::all()),
         );
-        let st = Style::default().fg(Color::Rgb(200, 200, 100));
-        date.items = self
+        let highlighter = self
+            .container
+            .search
+            .as_ref()
+            .and_then(ListSearch::highlighter);
+        content.items = self
             .container
             .items
             .iter()
             .cloned()
-            .map(|id| {
-                context
-                    .content_register
+            .enumerate()
+            .map(|(i, id)| {
+                register
                     .get(id)
                     .map(|e| {
-                        e.as_any()
-                            .downcast_ref::<Todo>()
-                            .unwrap()
-                            .due_date
-                            .map(NaiveDate::from)
-                            .map(|d| d.format("%d-%m-%Y").to_string())
+                        let mut item = match e.as_any().downcast_ref::<Todo>() {
+                            Some(t) => t.item(tags, tracked),
+                            None => e.as_display().display(),
+                        };
+                        if self.container.is_marked(i) {
+                            item.mark(Marker {
+                                symbol: Span::styled("+", theme().marked),
+                                pos: MarkerPos::Left,
+                            });
+                        }
+                        if let Some(re) = &highlighter {
+                            item.highlight(
+                                &matched_chars(re, &e.as_display().text()),
+                                theme().matched,
+                            );
+                        }
+                        item
                     })
-                    .flatten()
-                    .map(Cow::from)
-                    .unwrap_or(Cow::from(""))
-            })
-            .map(Span::raw)
-            .map(Line::new)
-            .map(|mut l| {
-                l.text_style(st);
-                Item {
-                    text: vec![l],
-                    selected_text: SelectedText::Style(st.add_modifier(Modifier::BOLD)),
-                }
+                    .unwrap()
             })
             .collect();
-
-        if self.insert_mode.is_listening() {
-            let line = self.insert_mode.line();
-            let item = Item {
-                text: vec![line.clone()],
-                selected_text: SelectedText::Lines(vec![line]),
-            };
-            match self.listen_target {
-                ListenTarget::ContentCreate | ListenTarget::ContentEdit => {
-                    content.items[self.container.selected_index.selected_index()] = item;
-                }
-                ListenTarget::DueDate => {
-                    date.items[self.container.selected_index.selected_index()] = item;
-                }
-                ListenTarget::DueTime => todo!(),
-                ListenTarget::None => unreachable!(),
-            }
-        }
-        WidgetOutput::TodoList { content, date }
+        content
     }
 }
 
@@ -430,11 +908,11 @@ impl Display for TodoList {
     }
     fn display(&self) -> Self::Output {
         let mut text = Line::new(Span::raw(self.text()));
-        let st = Style::default().fg(Color::Rgb(200, 200, 100));
+        let st = theme().text;
         text.text_style(st);
         Item {
             text: vec![text],
-            selected_text: SelectedText::Style(st.add_modifier(Modifier::BOLD)),
+            selected_text: SelectedText::Style(st.patch(theme().selected)),
         }
     }
     fn set_text(&mut self, name: Cow<'static, str>) {
@@ -469,7 +947,284 @@ impl<'a> Provider<'a> for TodoList {
     }
 }
 
+impl Commands for TodoList {
+    fn commands(&self) -> Vec<CommandInfo> {
+        vec![
+            CommandInfo {
+                name: "add",
+                args: "",
+                description: "add a todo below the selected one (a)",
+            },
+            CommandInfo {
+                name: "edit",
+                args: "",
+                description: "edit the selected todo (i)",
+            },
+            CommandInfo {
+                name: "done",
+                args: "",
+                description: "mark the selected or marked todos done or pending (c)",
+            },
+            CommandInfo {
+                name: "delete",
+                args: "",
+                description: "delete the selected or marked todos with their subtasks (x)",
+            },
+            CommandInfo {
+                name: "move",
+                args: "<list>",
+                description: "move the selected or marked todos to the end of another list",
+            },
+            CommandInfo {
+                name: "tag",
+                args: "<tag>",
+                description: "tag the selected or marked todos",
+            },
+            CommandInfo {
+                name: "untag",
+                args: "<tag>",
+                description: "remove the tag from the selected or marked todos",
+            },
+            CommandInfo {
+                name: "yank",
+                args: "",
+                description: "yank the selected or marked todos (y)",
+            },
+            CommandInfo {
+                name: "paste",
+                args: "",
+                description: "paste copies of the yanked todos below the selected one (ctrl-v)",
+            },
+            CommandInfo {
+                name: "timer",
+                args: "",
+                description: "start or stop the timer of the selected todo (t)",
+            },
+            CommandInfo {
+                name: "due",
+                args: "[date|none]",
+                description: "set the due date of the selected or marked todos (d)",
+            },
+            CommandInfo {
+                name: "priority",
+                args: "high|medium|low|none",
+                description: "set the priority of the selected or marked todos",
+            },
+            CommandInfo {
+                name: "sort",
+                args: "due|status|name|created|priority",
+                description: "sort the list",
+            },
+            CommandInfo {
+                name: "columns",
+                args: "[title:2 due:1 ...|default]",
+                description: "choose the columns of the list and their widths",
+            },
+        ]
+    }
+
+    fn run_command(&mut self, name: &str, args: &str, self_id: Id) -> Option<AppAction> {
+        let selected = self.get_selected();
+        let targets = self.container.selection();
+        let mut key = |c| {
+            let a = match self
+                .handle_events(&key_press(KeyCode::Char(c), KeyModifiers::NONE), self_id)
+            {
+                EventAction::Absorbed(a) | EventAction::Unabsorbed(a) => a,
+            };
+            Some(a)
+        };
+        match (name, args) {
+            (_, _)
+                if selected.is_none() && !["add", "sort", "paste", "columns"].contains(&name) =>
+            {
+                Some(AppAction::error("no todo selected"))
+            }
+            ("add", _) => key('a'),
+            ("edit", _) => key('i'),
+            ("done", _) => key('c'),
+            ("timer", _) => key('t'),
+            ("delete", _) => key('x'),
+            ("yank", _) => key('y'),
+            ("paste", _) => Some(paste(self_id)),
+            ("move" | "tag" | "untag" | "priority", "") => {
+                Some(AppAction::error(format!("{name} what?")))
+            }
+            ("move", list) => {
+                self.container.clear_marks();
+                Some(move_to(self_id, targets, list.to_owned()))
+            }
+            ("tag" | "untag", t) => {
+                self.container.clear_marks();
+                Some(tag(
+                    targets,
+                    t.trim_start_matches('#').to_owned(),
+                    name == "tag",
+                ))
+            }
+            ("due", "") => key('d'),
+            ("due", date) => {
+                let date = match date {
+                    "none" => None,
+                    date => match parse_date(date) {
+                        Some(date) => Some(Date::from(date)),
+                        None => return Some(not_a_date(date)),
+                    },
+                };
+                self.container.clear_marks();
+                Some(set_due(targets, date))
+            }
+            ("priority", p) => {
+                let priority = match (p, Priority::parse(p)) {
+                    ("none", _) => None,
+                    (_, Some(p)) => Some(p),
+                    (p, None) => {
+                        return Some(AppAction::error(format!(
+                            "not a priority: {p}, expected high, medium, low or none"
+                        )))
+                    }
+                };
+                self.container.clear_marks();
+                Some(set_priority(targets, priority))
+            }
+            ("sort", by) => Some(sort(self_id, by.to_owned())),
+            ("columns", "") => Some(AppAction::info(format!(
+                "columns: {}",
+                ListColumn::format_all(&self.columns)
+            ))),
+            ("columns", columns) => {
+                let columns = match columns {
+                    "default" => Ok(ListColumn::defaults()),
+                    columns => ListColumn::parse_all(columns),
+                };
+                Some(match columns {
+                    Ok(columns) => {
+                        self.columns = columns;
+                        AppAction::info(format!(
+                            "columns: {}",
+                            ListColumn::format_all(&self.columns)
+                        ))
+                    }
+                    Err(err) => AppAction::error(err.to_string()),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// stable, so todos that compare equal keep their order
+fn sort(self_id: Id, by: String) -> AppAction {
+    if !["due", "status", "name", "created", "priority"].contains(&by.as_str()) {
+        return AppAction::error(format!(
+            "can't sort by {by:?}, use due, status, name, created or priority"
+        ));
+    }
+    AppAction::Callback {
+        call: Box::new(move |ctx| {
+            let register = &*ctx.register;
+            let todo = |id: &Id| {
+                register
+                    .get(*id)
+                    .and_then(|c| c.as_any().downcast_ref::<Todo>())
+            };
+            let me = register
+                .get(self_id)
+                .unwrap()
+                .as_any()
+                .downcast_ref::<TodoList>()
+                .unwrap();
+            let selected = me.get_selected();
+            let mut items = me.items().to_vec();
+            match by.as_str() {
+                // todos without a due date last
+                "due" => items.sort_by_key(|id| {
+                    let due = todo(id).and_then(Todo::due);
+                    (due.is_none(), due)
+                }),
+                "status" => items.sort_by_key(|id| todo(id).map(|t| t.status as u8)),
+                // highest first, todos without a priority last
+                "priority" => {
+                    items.sort_by_key(|id| std::cmp::Reverse(todo(id).and_then(|t| t.priority)))
+                }
+                "name" => items.sort_by_cached_key(|id| {
+                    register
+                        .get(*id)
+                        .map(|c| c.as_display().text().to_lowercase())
+                }),
+                _ => items.sort_by_key(|id| todo(id).and_then(|t| t.created_at)),
+            }
+            let me = ctx
+                .register
+                .get_mut(self_id)
+                .unwrap()
+                .as_any_mut()
+                .downcast_mut::<TodoList>()
+                .unwrap();
+            me.container.items = items;
+            if let Some(i) = selected.and_then(|s| me.items().iter().position(|&id| id == s)) {
+                me.container.selected_index.select(i);
+            }
+            Ok(AppAction::info(format!("sorted by {by}")))
+        }),
+    }
+}
+
+impl Status for TodoList {
+    fn mode(&self) -> Mode {
+        match (self.insert_mode.is_listening(), self.listen_target) {
+            (true, ListenTarget::Search) => Mode::Filter,
+            (true, _) => Mode::Insert,
+            (false, _) if self.container.visual.is_some() => Mode::Visual,
+            (false, _) => Mode::Normal,
+        }
+    }
+    fn pending_keys(&self) -> String {
+        self.container.pending.clone()
+    }
+    fn counted(&self) -> &[Id] {
+        self.items()
+    }
+}
+
 #[typetag::serde]
 impl ContentTrait for TodoList {
-    impliment_content!(TodoList, Widget, EventHandler, Display, YankDest, Provider);
+    impliment_content!(
+        TodoList,
+        Widget,
+        EventHandler,
+        Display,
+        YankDest,
+        Provider,
+        Status,
+        Commands
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delete_removes_the_todo_and_its_subtasks() {
+        let mut register = ContentRegister::<Content, Id>::new();
+        let sub = register.alloc(Content::from(Todo::new("sub")));
+        let mut list = TodoList::new("subtasks");
+        list.container.items.push(sub);
+        let list = register.alloc(Content::from(list));
+        let mut todo = Todo::new("todo");
+        todo.subtasks = Some(list);
+        let id = register.alloc(Content::from(todo));
+        register.take_touched();
+
+        delete_todo(&mut register, id);
+        assert!([id, list, sub].iter().all(|&i| register.get(i).is_none()));
+        assert_eq!(register.iter().count(), 0);
+        let gone = register
+            .take_touched()
+            .into_iter()
+            .filter(|(_, before, now)| before.is_some() && now.is_none())
+            .count();
+        assert_eq!(gone, 3);
+    }
 }
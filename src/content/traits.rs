@@ -3,9 +3,11 @@ use crate::{
     display::{Item, ListBuilder},
     key,
     register::{ContentRegister, Id},
-    service::editors::Yank,
+    service::{config::config, editors::Yank},
+    shift,
     traits::{
-        Display, Drawable, EventAction, EventHandler, Provider, SelectedIndex, Widget, YankDest,
+        Align, Commands, Display, Drawable, EventAction, EventHandler, Provider, SelectedIndex,
+        Status, Widget, YankDest,
     },
 };
 #[allow(unused_imports)]
@@ -13,6 +15,7 @@ use crate::{dbg, debug, error};
 
 use anyhow::Result;
 use crossterm::event::Event;
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
 use std::{any::Any, borrow::Cow, fmt::Debug};
 use tui::{
@@ -108,6 +111,12 @@ where
     ) -> Option<&mut dyn Provider<Item = Option<Id>, Context = &mut SelectedIndex>> {
         None
     }
+    fn as_status(&self) -> Option<&'_ dyn Status> {
+        None
+    }
+    fn as_commands(&mut self) -> Option<&'_ mut dyn Commands> {
+        None
+    }
 }
 
 #[macro_export]
@@ -127,6 +136,12 @@ macro_rules! impliment_content {
         fn as_display(&self) -> &dyn Display<Output = Item<'static>> {self}
         fn as_display_mut(&mut self) -> &mut dyn Display<Output = Item<'static>> {self}
     };
+    ($t:ident, Status) => {
+        fn as_status(&self) -> Option<&'_ dyn Status> {Some(self)}
+    };
+    ($t:ident, Commands) => {
+        fn as_commands(&mut self) -> Option<&'_ mut dyn Commands> {Some(self)}
+    };
     ($t:ident, Widget) => {
         fn as_widget(&self) -> Option<&dyn Widget<Context = DisplayContext, Output = WidgetOutput<'static>>> {Some(self)}
     };
@@ -149,12 +164,189 @@ pub struct Container<T> {
     pub items: Vec<T>,
     #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
     pub selected_index: SelectedIndex,
+    /// the search typed with / (or the last one), its matches are highlighted
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    pub search: Option<ListSearch>,
+    /// items toggled with space
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    pub marked: Vec<T>,
+    /// where the range started with V begins
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    pub visual: Option<usize>,
+    /// a count and g typed before a motion, eg the 12 of 12G
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    pub pending: String,
 }
 impl<T> Default for Container<T> {
     fn default() -> Self {
         Self {
             items: Default::default(),
             selected_index: Default::default(),
+            search: None,
+            marked: Default::default(),
+            visual: None,
+            pending: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ListSearch {
+    pub query: String,
+    pub regex: bool,
+    pub ignore_case: bool,
+    /// the selection when the search started, where it goes back to on cancel
+    pub origin: usize,
+}
+
+impl ListSearch {
+    pub fn new(origin: usize) -> Self {
+        Self {
+            query: String::new(),
+            regex: config().search.regex,
+            ignore_case: config().search.ignore_case,
+            origin,
+        }
+    }
+
+    /// None if the query is not a valid regex
+    pub fn matcher(&self) -> Option<Regex> {
+        let pattern = match self.regex {
+            true => self.query.clone(),
+            false => regex::escape(&self.query),
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(self.ignore_case)
+            .build()
+            .ok()
+    }
+
+    /// the matcher to highlight with, None while nothing is typed
+    pub fn highlighter(&self) -> Option<Regex> {
+        self.matcher().filter(|_| !self.query.is_empty())
+    }
+}
+
+/// indices of the chars (not bytes) in the matches of the regex in the text
+pub fn matched_chars(re: &Regex, text: &str) -> Vec<usize> {
+    let ranges = re
+        .find_iter(text)
+        .filter(|m| !m.as_str().is_empty())
+        .map(|m| m.range())
+        .collect::<Vec<_>>();
+    text.char_indices()
+        .enumerate()
+        .filter(|(_, (b, _))| ranges.iter().any(|r| r.contains(b)))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Jump {
+    /// the first match from where the search started, while typing
+    First,
+    Next,
+    Prev,
+}
+
+impl<T> Container<T> {
+    /// moves the selection to a match of the search in the texts of the items. wraps around. false if nothing matches
+    pub fn jump_to_match(&mut self, texts: &[String], jump: Jump) -> bool {
+        let search = match &self.search {
+            Some(s) => s,
+            None => return false,
+        };
+        let matcher = search.matcher();
+        let matches = texts
+            .iter()
+            .enumerate()
+            .filter(|(_, text)| matcher.as_ref().is_some_and(|m| m.is_match(text)))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let current = self.selected_index.selected_index();
+        let target = match jump {
+            Jump::First => matches.iter().find(|&&i| i >= search.origin),
+            Jump::Next => matches.iter().find(|&&i| i > current),
+            Jump::Prev => matches.iter().rev().find(|&&i| i < current),
+        };
+        let target = match jump {
+            Jump::Prev => target.or(matches.last()),
+            _ => target.or(matches.first()),
+        };
+        match target {
+            Some(&i) => self.selected_index.select(i),
+            None if jump == Jump::First => self.selected_index.select(search.origin),
+            None => (),
+        }
+        target.is_some()
+    }
+}
+impl<T: PartialEq + Copy> Container<T> {
+    pub fn toggle_mark(&mut self) {
+        if let Some(&id) = self.items.get(self.selected_index.selected_index()) {
+            match self.marked.iter().position(|&m| m == id) {
+                Some(i) => {
+                    self.marked.remove(i);
+                }
+                None => self.marked.push(id),
+            }
+        }
+    }
+
+    /// starts a range at the selection. ending it keeps the range marked
+    pub fn toggle_visual(&mut self) {
+        match self.visual {
+            Some(_) => {
+                self.marked = self.selection();
+                self.visual = None;
+            }
+            None => self.visual = Some(self.selected_index.selected_index()),
+        }
+    }
+
+    pub fn has_marks(&self) -> bool {
+        self.visual.is_some() || !self.marked.is_empty()
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
+        self.visual = None;
+    }
+
+    /// marked with space or inside the V range
+    pub fn is_marked(&self, index: usize) -> bool {
+        let current = self.selected_index.selected_index();
+        self.visual
+            .is_some_and(|start| (start.min(current)..=start.max(current)).contains(&index))
+            || self
+                .items
+                .get(index)
+                .is_some_and(|id| self.marked.contains(id))
+    }
+
+    /// the marked items in list order, or the selected one if nothing is marked
+    pub fn selection(&self) -> Vec<T> {
+        let marked = (0..self.items.len())
+            .filter(|&i| self.is_marked(i))
+            .map(|i| self.items[i])
+            .collect::<Vec<_>>();
+        if !marked.is_empty() {
+            return marked;
+        }
+        self.items
+            .get(self.selected_index.selected_index())
+            .copied()
+            .into_iter()
+            .collect()
+    }
+
+    /// takes the items out, the selection stays inside the list
+    pub fn remove_all(&mut self, ids: &[T]) {
+        self.items.retain(|id| !ids.contains(id));
+        self.marked.retain(|id| !ids.contains(id));
+        let last = self.items.len().saturating_sub(1);
+        if self.selected_index.selected_index() > last {
+            self.selected_index.select(last);
         }
     }
 }
@@ -163,21 +355,28 @@ impl<'a, T> EventHandler<'a> for Container<T> {
     type Context = ();
     fn handle_events(&mut self, event: &Event, _ctx: Self::Context) -> Self::Action {
         let unabsorbed = EventAction::Unabsorbed(AppAction::None);
+        // any other key drops the count
+        let pending = std::mem::take(&mut self.pending);
+        let count = pending.trim_end_matches(['g', 'z']).parse::<usize>().ok();
+        let current = self.selected_index.selected_index();
+        let last = self.items.len().saturating_sub(1);
+        // 1 based, like the line numbers
+        let line = |n: usize| n.saturating_sub(1).min(last);
+        let page = (self.selected_index.visible() * count.unwrap_or(1)) as isize;
         match event {
             Event::Key(k) => match k {
-                key!(Up) => {
+                key!('0') if pending.is_empty() => return unabsorbed,
+                key!(c @ '0'..='9') if !pending.ends_with('g') => {
+                    self.pending = format!("{pending}{c}");
+                }
+                key!(Up) | key!('k') => {
                     self.selected_index
-                        .select(if self.selected_index.selected_index() > 0 {
-                            self.selected_index.selected_index() - 1
-                        } else {
-                            0
-                        });
+                        .select(current.saturating_sub(count.unwrap_or(1)));
                 }
-                key!(Down) => {
+                key!(Down) | key!('j') => {
                     if self.items.len() > 0 {
-                        self.selected_index.select(
-                            (self.items.len() - 1).min(self.selected_index.selected_index() + 1),
-                        );
+                        self.selected_index
+                            .select(last.min(current + count.unwrap_or(1)));
                     }
                 }
                 key!(Home) => {
@@ -188,6 +387,35 @@ impl<'a, T> EventHandler<'a> for Container<T> {
                         self.selected_index.select(self.items.len() - 1);
                     }
                 }
+                shift!('G') => {
+                    self.selected_index.select(count.map_or(last, line));
+                }
+                key!('g') if pending.ends_with('g') => {
+                    self.selected_index.select(count.map_or(0, line));
+                }
+                key!('g') => {
+                    self.pending = format!("{pending}g");
+                }
+                key!(PageDown) => {
+                    self.selected_index.page(page, self.items.len());
+                }
+                key!(PageUp) => {
+                    self.selected_index.page(-page, self.items.len());
+                }
+                key!(c @ ('z' | 't' | 'b')) if pending.ends_with('z') => {
+                    let align = match c {
+                        'z' => Align::Center,
+                        't' => Align::Top,
+                        _ => Align::Bottom,
+                    };
+                    if let Some(n) = count {
+                        self.selected_index.select(line(n));
+                    }
+                    self.selected_index.align(align);
+                }
+                key!('z') => {
+                    self.pending = format!("{pending}z");
+                }
                 _ => return unabsorbed,
             },
             _ => return unabsorbed,
@@ -237,13 +465,8 @@ pub struct DrawContext<'a> {
 }
 
 pub enum WidgetOutput<'a> {
-    TodoList {
-        content: ListBuilder<'a>,
-        date: ListBuilder<'a>,
-    },
-    MainProvider {
-        content: ListBuilder<'a>,
-    },
+    Table(Table<'a>),
+    MainProvider { content: ListBuilder<'a> },
 }
 
 // pub enum DisplayOutput<'a> {
@@ -261,26 +484,76 @@ impl<'a> Drawable for WidgetOutput<'a> {
 
     fn draw<B: Backend>(&self, f: &mut Frame<B>, input: Self::Context) {
         match self {
-            WidgetOutput::TodoList { content, date } => {
-                let chunks = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .margin(0)
-                    .constraints([Constraint::Ratio(2, 3), Constraint::Ratio(1, 3)].as_ref())
-                    .split(input.area);
-                let content_area = chunks[0];
-                let date_area = chunks[1];
-
-                let content_list =
-                    content.list(content_area, input.selected_index.selected_index());
-                let date_list = date.list(date_area, input.selected_index.selected_index());
-
-                f.render_stateful_widget(content_list, content_area, input.selected_index.into());
-                f.render_stateful_widget(date_list, date_area, input.selected_index.into());
-            }
+            WidgetOutput::Table(table) => table.draw(f, input),
             WidgetOutput::MainProvider { content } => {
-                let content_list = content.list(input.area, input.selected_index.selected_index());
-                f.render_stateful_widget(content_list, input.area, input.selected_index.into());
+                draw_lists(f, &[(content, input.area)], input.selected_index);
             }
         }
     }
 }
+
+/// lists side by side, one per column, with an item in each for every row of the table
+#[derive(Default)]
+pub struct Table<'a> {
+    /// the list and its width relative to the others
+    pub columns: Vec<(ListBuilder<'a>, u16)>,
+}
+
+impl<'a> Drawable for Table<'a> {
+    type Context = DrawContext<'a>;
+
+    fn draw<B: Backend>(&self, f: &mut Frame<B>, input: Self::Context) {
+        let total = self
+            .columns
+            .iter()
+            .map(|(_, w)| *w as u32)
+            .sum::<u32>()
+            .max(1);
+        let constraints = self
+            .columns
+            .iter()
+            .map(|(_, w)| Constraint::Ratio(*w as u32, total))
+            .collect::<Vec<_>>();
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .margin(0)
+            .constraints(constraints)
+            .split(input.area);
+        let lists = self
+            .columns
+            .iter()
+            .zip(chunks)
+            .map(|((list, _), area)| (list, area))
+            .collect::<Vec<_>>();
+        if !lists.is_empty() {
+            draw_lists(f, &lists, input.selected_index);
+        }
+    }
+}
+
+/// draws lists side by side, scrolled together with the items of each in line
+fn draw_lists<B: Backend>(
+    f: &mut Frame<B>,
+    lists: &[(&ListBuilder, Rect)],
+    selected_index: &mut SelectedIndex,
+) {
+    let selected = selected_index.selected_index();
+    let mut rows = vec![0; lists[0].0.items.len()];
+    for (list, area) in lists {
+        for (r, n) in rows.iter_mut().zip(list.rows(*area, selected)) {
+            *r = n.max(*r);
+        }
+    }
+    let height = lists[0].0.get_inner_rect(lists[0].1).height;
+    selected_index.scroll(&rows, height.into());
+    let offset = selected_index.offset();
+    for (list, area) in lists {
+        let list = list.list_with_rows(*area, selected, &rows, offset);
+        f.render_stateful_widget(list, *area, &mut selected_index.state());
+    }
+    let areas = lists
+        .iter()
+        .map(|(list, area)| (*area, list.get_inner_rect(*area)))
+        .collect();
+    selected_index.set_drawn(areas, rows[offset..].to_vec());
+}
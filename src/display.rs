@@ -9,6 +9,12 @@ use tui::{
     widgets::{Block, BorderType, Borders, List, ListItem},
     Frame,
 };
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::service::{
+    config::{config, LineNumbers, LongLines},
+    theme::theme,
+};
 
 #[derive(Default, Debug)]
 pub struct ListBuilder<'a> {
@@ -18,10 +24,29 @@ pub struct ListBuilder<'a> {
     block: Option<Block<'a>>,
 }
 impl<'a> ListBuilder<'a> {
-    pub fn list<'c>(&'c self, rect: Rect, selected_index: usize) -> List<'a> {
+    /// items get at least as many rows as the same item of another list, so lists side by side stay in line.
+    /// the items before offset are left out
+    pub fn list_with_rows<'c>(
+        &'c self,
+        rect: Rect,
+        selected_index: usize,
+        rows: &[usize],
+        offset: usize,
+    ) -> List<'a> {
         let items = self.texts(rect, selected_index);
 
-        let items = items.into_iter().map(ListItem::new).collect::<Vec<_>>();
+        let items = items
+            .into_iter()
+            .enumerate()
+            .skip(offset)
+            .map(|(i, mut text)| {
+                let rows = rows.get(i).copied().unwrap_or(0);
+                while text.lines.len() < rows {
+                    text.lines.push(Spans::default());
+                }
+                ListItem::new(text)
+            })
+            .collect::<Vec<_>>();
 
         let mut list = List::new(items);
         if self.block.is_some() {
@@ -31,19 +56,65 @@ impl<'a> ListBuilder<'a> {
     }
     fn texts<'c>(&'c self, rect: Rect, selected_index: usize) -> Vec<Text<'a>> {
         let rect = self.get_inner_rect(rect);
+        let gutter = self.gutter_width();
 
         let items = self
             .items
             .iter()
             .enumerate()
-            .map(|(index, item)| (index == selected_index, item))
-            .map(|(selected, item)| item.text(rect.width, selected))
+            .map(|(index, item)| {
+                let mut text =
+                    item.text(rect.width.saturating_sub(gutter), index == selected_index);
+                if gutter > 0 {
+                    self.number(&mut text, index, selected_index, gutter);
+                }
+                text
+            })
             .collect::<Vec<_>>();
 
         items
     }
+    /// columns taken by the line numbers and the space after them
+    pub fn gutter_width(&self) -> u16 {
+        match (self.numbered, config().line_numbers) {
+            (false, _) | (_, LineNumbers::Off) => 0,
+            // relative numbers are never bigger than the count
+            _ => self.items.len().max(1).to_string().len() as u16 + 1,
+        }
+    }
+    /// puts the number in front of the first row of the item, the other rows are indented
+    fn number(&self, text: &mut Text<'a>, index: usize, selected_index: usize, gutter: u16) {
+        let n = match config().line_numbers {
+            LineNumbers::Relative if index != selected_index => index.abs_diff(selected_index),
+            _ => index + 1,
+        };
+        let style = match index == selected_index {
+            true => theme().text.patch(theme().selected),
+            false => theme().dim,
+        };
+        let width = gutter as usize - 1;
+        for (row, spans) in text.lines.iter_mut().enumerate() {
+            let number = match row {
+                0 => Span::styled(format!("{n:>width$} "), style),
+                _ => Span::raw(" ".repeat(width + 1)),
+            };
+            spans.0.insert(0, number);
+        }
+    }
+    /// rows each item takes when wrapped
+    pub fn rows(&self, rect: Rect, selected_index: usize) -> Vec<usize> {
+        self.texts(rect, selected_index)
+            .iter()
+            .map(|t| t.lines.len())
+            .collect()
+    }
+    /// the theme's title style goes under the styles of the spans
     pub fn title<'b: 'c + 'a, 'c, T: Into<Spans<'b>>>(&'c mut self, title: T) -> &mut Self {
-        self.title = Some(title.into());
+        let mut title = title.into();
+        for span in title.0.iter_mut() {
+            span.style = theme().title.patch(span.style);
+        }
+        self.title = Some(title);
         let block = self.block.take().unwrap_or(
             Block::default()
                 .borders(Borders::all())
@@ -85,6 +156,27 @@ pub struct Pos {
     pub inner_rect: Rect,
 }
 
+/// the text with the chars at the indices (of chars, not bytes) in the matched style
+pub fn highlight(text: &str, indices: &[usize], style: Style, matched: Style) -> Spans<'static> {
+    let mut spans: Vec<Span<'static>> = vec![];
+    let mut current = String::new();
+    let mut current_matched = false;
+    for (i, c) in text.chars().enumerate() {
+        let is_matched = indices.contains(&i);
+        if is_matched != current_matched && !current.is_empty() {
+            let st = if current_matched { matched } else { style };
+            spans.push(Span::styled(std::mem::take(&mut current), st));
+        }
+        current_matched = is_matched;
+        current.push(c);
+    }
+    if !current.is_empty() {
+        let st = if current_matched { matched } else { style };
+        spans.push(Span::styled(current, st));
+    }
+    Spans::from(spans)
+}
+
 /// mark a item with some symbol/style/color to indicate something
 #[derive(Debug, Clone)]
 pub struct Marker<'a> {
@@ -106,8 +198,8 @@ impl<'a> Item<'a> {
                     let spans: Text = self
                         .text
                         .iter()
-                        .map(|l| {
-                            let mut spans = l.spans(width);
+                        .flat_map(|l| l.rows(width))
+                        .map(|mut spans| {
                             spans.0.iter_mut().for_each(|s| s.style = style.clone());
                             spans
                         })
@@ -118,14 +210,14 @@ impl<'a> Item<'a> {
                 }
                 SelectedText::Lines(text) => text
                     .iter()
-                    .map(|l| l.spans(width))
+                    .flat_map(|l| l.rows(width))
                     .collect::<Vec<_>>()
                     .into(),
             }
         } else {
             self.text
                 .iter()
-                .map(|l| l.spans(width))
+                .flat_map(|l| l.rows(width))
                 .collect::<Vec<_>>()
                 .into()
         }
@@ -135,6 +227,30 @@ impl<'a> Item<'a> {
         self.text.iter_mut().for_each(|l| l.text_style(s));
     }
 
+    /// patches the style over the chars at the indices of the main text of the first line, e.g. search matches
+    pub fn highlight(&mut self, indices: &[usize], style: Style) {
+        if let Some(l) = self.text.first_mut() {
+            l.highlight(indices, style);
+        }
+        if let SelectedText::Lines(lines) = &mut self.selected_text {
+            if let Some(l) = lines.first_mut() {
+                l.highlight(indices, style);
+            }
+        }
+    }
+
+    /// adds the marker to the first line, selected or not
+    pub fn mark(&mut self, marker: Marker<'a>) {
+        if let Some(l) = self.text.first_mut() {
+            l.markers.push(marker.clone());
+        }
+        if let SelectedText::Lines(lines) = &mut self.selected_text {
+            if let Some(l) = lines.first_mut() {
+                l.markers.push(marker);
+            }
+        }
+    }
+
     pub fn selected_text_style(&mut self, new_style: Style) {
         match &mut self.selected_text {
             SelectedText::Style(s) => *s = new_style,
@@ -170,6 +286,21 @@ impl<'a> Line<'a> {
             .map(|spans| spans.0.iter_mut().for_each(|span| span.style = s.clone()));
     }
 
+    /// patches the style over the chars at the indices of the first span of the main text
+    pub fn highlight(&mut self, indices: &[usize], style: Style) {
+        let first = match self.main_text.0.first() {
+            Some(s) if !indices.is_empty() => s.clone(),
+            _ => return,
+        };
+        let spans = highlight(
+            &first.content,
+            indices,
+            first.style,
+            first.style.patch(style),
+        );
+        self.main_text.0.splice(0..1, spans.0);
+    }
+
     pub fn overwrite_style(&mut self, s: Style) {
         self.text_style(s);
         self.markers
@@ -177,34 +308,184 @@ impl<'a> Line<'a> {
             .for_each(|m| m.symbol.style = s.clone());
     }
 
-    fn spans(&self, _width: u16) -> Spans<'a> {
-        match self.main_text_alignment {
-            Alignment::Left => {
-                let mut spans = match &self.secondary_text {
-                    Some(s) => s.clone(),
-                    None => self.main_text.clone(),
+    /// the line laid out in rows of the width. main text and secondary text share a row if they fit, long main
+    /// text is wrapped or truncated as configured
+    fn rows(&self, width: u16) -> Vec<Spans<'a>> {
+        let marker = |pos| {
+            self.markers
+                .iter()
+                .rev()
+                .filter(|m| {
+                    matches!(
+                        (&m.pos, pos),
+                        (MarkerPos::Left, true) | (MarkerPos::Right, false)
+                    )
+                })
+                .map(|m| m.symbol.clone())
+                .collect::<Vec<_>>()
+        };
+        let mut left = marker(true);
+        if !left.is_empty() {
+            left.push(" ".into());
+        }
+        let mut right = marker(false);
+        if !right.is_empty() {
+            right.insert(0, " ".into());
+        }
+        let indent = spans_width(&left);
+        let avail = (width as usize).saturating_sub(indent + spans_width(&right));
+        let main = &self.main_text.0;
+        let secondary = self
+            .secondary_text
+            .as_ref()
+            .map(|s| s.0.clone())
+            .unwrap_or_default();
+        // the secondary text only stays if the main text keeps at least half the row
+        let secondary = match spans_width(&secondary) {
+            0 => vec![],
+            w if w < avail / 2 || spans_width(main) + w < avail => secondary,
+            _ => vec![],
+        };
+        let main_avail = match secondary.is_empty() {
+            true => avail,
+            false => avail - spans_width(&secondary) - 1,
+        };
+        let mut main_rows = match config().long_lines {
+            _ if spans_width(main) <= main_avail => vec![main.clone()],
+            LongLines::Truncate => vec![truncate(main, main_avail)],
+            LongLines::Wrap => wrap(main, main_avail),
+        };
+        let last = main_rows.pop().unwrap_or_default();
+        let mut rows = main_rows
+            .into_iter()
+            .map(|row| self.align(row, vec![], avail))
+            .collect::<Vec<_>>();
+        rows.push(self.align(last, secondary, avail));
+
+        let pad = Span::raw(" ".repeat(indent));
+        rows.into_iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let mut spans = match i {
+                    0 => left.clone(),
+                    _ => vec![pad.clone()],
                 };
-                if self.markers.len() > 0 {
-                    spans.0.insert(0, " ".into());
+                spans.extend(row);
+                if i == 0 {
+                    spans.extend(right.clone());
                 }
-                for marker in self.markers.iter() {
-                    match marker.pos {
-                        MarkerPos::Left => {
-                            spans.0.insert(0, marker.symbol.clone());
-                        }
-                        MarkerPos::Right => {
-                            todo!()
-                        }
-                    }
+                Spans::from(spans)
+            })
+            .collect()
+    }
+
+    /// main text and secondary text on opposite sides of a row of the width
+    fn align(&self, main: Vec<Span<'a>>, secondary: Vec<Span<'a>>, width: usize) -> Vec<Span<'a>> {
+        let gap = width.saturating_sub(spans_width(&main) + spans_width(&secondary));
+        let space = |n: usize| Span::raw(" ".repeat(n));
+        match self.main_text_alignment {
+            Alignment::Left => [main, vec![space(gap)], secondary].concat(),
+            Alignment::Right => [secondary, vec![space(gap)], main].concat(),
+            Alignment::Centered if secondary.is_empty() => {
+                [vec![space(gap / 2)], main, vec![space(gap - gap / 2)]].concat()
+            }
+            // centered in the row, the secondary text at the end if there is room for it
+            Alignment::Centered => {
+                let main_width = spans_width(&main);
+                let before = width.saturating_sub(main_width) / 2;
+                let after = width.saturating_sub(before + main_width + spans_width(&secondary));
+                match after {
+                    0 => [main, vec![space(1)], secondary].concat(),
+                    after => [vec![space(before)], main, vec![space(after)], secondary].concat(),
                 }
-                spans
             }
-            Alignment::Centered => todo!(),
-            Alignment::Right => todo!(),
         }
     }
 }
 
+fn spans_width(spans: &[Span]) -> usize {
+    spans.iter().map(|s| s.content.width()).sum()
+}
+
+/// the chars of the spans with their styles
+fn styled_chars(spans: &[Span]) -> Vec<(char, Style)> {
+    spans
+        .iter()
+        .flat_map(|s| s.content.chars().map(move |c| (c, s.style)))
+        .collect()
+}
+
+/// joins chars of the same style back into spans
+fn join_chars<'a>(chars: &[(char, Style)]) -> Vec<Span<'a>> {
+    let mut spans: Vec<Span> = vec![];
+    for &(c, style) in chars {
+        match spans.last_mut() {
+            Some(s) if s.style == style => s.content.to_mut().push(c),
+            _ => spans.push(Span::styled(c.to_string(), style)),
+        }
+    }
+    spans
+}
+
+/// cut to the width, with … in place of what did not fit
+fn truncate<'a>(spans: &[Span<'a>], width: usize) -> Vec<Span<'a>> {
+    let chars = styled_chars(spans);
+    let mut used = 0;
+    let mut end = 0;
+    for &(c, _) in &chars {
+        let w = c.width().unwrap_or(0);
+        if used + w >= width {
+            break;
+        }
+        used += w;
+        end += 1;
+    }
+    let mut cut = chars[..end].to_vec();
+    if width > 0 {
+        let style = chars.get(end).map_or(Style::default(), |&(_, s)| s);
+        cut.push(('…', style));
+    }
+    join_chars(&cut)
+}
+
+/// rows of at most the width, broken at spaces where possible
+fn wrap<'a>(spans: &[Span<'a>], width: usize) -> Vec<Vec<Span<'a>>> {
+    let chars = styled_chars(spans);
+    if width == 0 {
+        return vec![spans.to_vec()];
+    }
+    let mut rows = vec![];
+    let mut start = 0;
+    while start < chars.len() {
+        let mut used = 0;
+        let mut end = start;
+        while end < chars.len() && used + chars[end].0.width().unwrap_or(0) <= width {
+            used += chars[end].0.width().unwrap_or(0);
+            end += 1;
+        }
+        // a char wider than the row still has to go somewhere
+        end = end.max(start + 1);
+        let mut next = end;
+        // the row ends right before a space, so the space is where it breaks
+        if chars.get(end).is_some_and(|&(c, _)| c == ' ') {
+            next = end + 1;
+        } else if end < chars.len() {
+            if let Some(space) = chars[start..end].iter().rposition(|&(c, _)| c == ' ') {
+                if space > 0 {
+                    end = start + space;
+                    next = end + 1;
+                }
+            }
+        }
+        rows.push(join_chars(&chars[start..end]));
+        start = next;
+    }
+    if rows.is_empty() {
+        rows.push(vec![]);
+    }
+    rows
+}
+
 #[derive(Debug, Clone)]
 pub enum SelectedText<'a> {
     Style(Style), // overrides all styles to this
@@ -230,3 +511,152 @@ pub enum MarkerPos {
     Left,
     Right,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tui::style::Color;
+
+    fn text(spans: &[Span]) -> String {
+        spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    fn rows(line: &Line, width: u16) -> Vec<String> {
+        line.rows(width).iter().map(|r| text(&r.0)).collect()
+    }
+
+    fn wrapped(s: &str, width: usize) -> Vec<String> {
+        wrap(&[Span::raw(s)], width)
+            .iter()
+            .map(|r| text(r))
+            .collect()
+    }
+
+    fn truncated(s: &str, width: usize) -> String {
+        text(&truncate(&[Span::raw(s)], width))
+    }
+
+    #[test]
+    fn highlight_takes_char_indices() {
+        let re = regex::Regex::new("d").unwrap();
+        let mut line = Line::new(Span::raw("äbcd"));
+        line.highlight(
+            &crate::content::traits::matched_chars(&re, "äbcd"),
+            Style::default().fg(Color::Red),
+        );
+        let spans = line.main_text.0;
+        assert_eq!(text(&spans), "äbcd");
+        assert_eq!(spans.last().unwrap().content, "d");
+        assert_eq!(spans.last().unwrap().style.fg, Some(Color::Red));
+    }
+
+    #[test]
+    fn wrap_breaks_at_spaces() {
+        assert_eq!(wrapped("hello world foo", 11), ["hello world", "foo"]);
+        assert_eq!(wrapped("hello world", 8), ["hello", "world"]);
+    }
+
+    #[test]
+    fn wrap_splits_words_longer_than_the_row() {
+        assert_eq!(wrapped("abcdefgh", 3), ["abc", "def", "gh"]);
+    }
+
+    #[test]
+    fn wrap_counts_wide_chars_twice() {
+        assert_eq!(wrapped("日本語", 4), ["日本", "語"]);
+        assert_eq!(wrapped("日本語", 5), ["日本", "語"]);
+        // a char wider than the row still gets one of its own
+        assert_eq!(wrapped("日本", 1), ["日", "本"]);
+    }
+
+    #[test]
+    fn wrap_zero_width_and_empty() {
+        assert_eq!(wrapped("abc", 0), ["abc"]);
+        assert_eq!(wrapped("", 5), [""]);
+    }
+
+    #[test]
+    fn wrap_keeps_styles() {
+        let red = Style::default().fg(Color::Red);
+        let rows = wrap(&[Span::raw("ab "), Span::styled("cd", red)], 3);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1], vec![Span::styled("cd", red)]);
+    }
+
+    #[test]
+    fn truncate_leaves_room_for_the_ellipsis() {
+        assert_eq!(truncated("hello world", 5), "hell…");
+        assert_eq!(truncated("日本語", 5), "日本…");
+        assert_eq!(truncated("日本語", 4), "日…");
+        assert_eq!(truncated("abc", 0), "");
+    }
+
+    #[test]
+    fn truncate_styles_the_ellipsis_like_what_it_replaces() {
+        let red = Style::default().fg(Color::Red);
+        let cut = truncate(&[Span::raw("ab"), Span::styled("cd", red)], 3);
+        assert_eq!(cut, vec![Span::raw("ab"), Span::styled("…", red)]);
+    }
+
+    // the config of the repo leaves long_lines at wrap
+    #[test]
+    fn rows_put_the_secondary_text_at_the_end() {
+        let mut line = Line::new("abc");
+        line.secondary_text = Some("12".into());
+        assert_eq!(rows(&line, 10), ["abc     12"]);
+    }
+
+    #[test]
+    fn rows_wrap_the_main_text_beside_the_secondary_text() {
+        let mut line = Line::new("abcdefghij");
+        line.secondary_text = Some("1234".into());
+        // 10 - 4 - 1 for the main text
+        assert_eq!(rows(&line, 10), ["abcde     ", "fghij 1234"]);
+    }
+
+    #[test]
+    fn rows_drop_the_secondary_text_if_it_takes_half_the_row() {
+        let mut line = Line::new("abcdefghij");
+        line.secondary_text = Some("123456".into());
+        assert_eq!(rows(&line, 10), ["abcdefghij"]);
+        // unless everything fits anyway
+        let mut line = Line::new("abc");
+        line.secondary_text = Some("123456".into());
+        assert_eq!(rows(&line, 10), ["abc 123456"]);
+    }
+
+    #[test]
+    fn rows_indent_wrapped_rows_past_the_markers() {
+        let mut line = Line::new("abcdef");
+        line.markers.push(Marker {
+            symbol: "▸".into(),
+            pos: MarkerPos::Left,
+        });
+        assert_eq!(rows(&line, 5), ["▸ abc", "  def"]);
+    }
+
+    fn aligned(alignment: Alignment, main: &str, secondary: &str, width: usize) -> String {
+        let mut line = Line::new(main);
+        line.main_text_alignment = alignment;
+        let secondary = match secondary {
+            "" => vec![],
+            s => vec![Span::raw(s)],
+        };
+        text(&line.align(vec![Span::raw(main)], secondary, width))
+    }
+
+    #[test]
+    fn align_puts_the_texts_on_opposite_sides() {
+        assert_eq!(aligned(Alignment::Left, "ab", "1", 6), "ab   1");
+        assert_eq!(aligned(Alignment::Right, "ab", "1", 6), "1   ab");
+        assert_eq!(aligned(Alignment::Left, "abcdef", "1", 6), "abcdef1");
+    }
+
+    #[test]
+    fn align_centers_the_main_text() {
+        assert_eq!(aligned(Alignment::Centered, "ab", "", 7), "  ab   ");
+        assert_eq!(aligned(Alignment::Centered, "ab", "1", 8), "   ab  1");
+        // no room after the centered text, the secondary text follows it
+        assert_eq!(aligned(Alignment::Centered, "abcd", "12", 6), "abcd 12");
+    }
+}
@@ -3,12 +3,15 @@
 #![allow(unused_imports)]
 
 mod app;
+mod cli;
 mod content;
 mod display;
+mod overlay;
 mod register;
 mod run;
 mod service;
 mod stack;
+mod status_bar;
 mod traits;
 
 pub use service::log::{
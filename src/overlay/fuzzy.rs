@@ -0,0 +1,184 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use crossterm::event::Event;
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use std::cmp::Reverse;
+use tui::{
+    style::Style,
+    text::{Span, Spans},
+};
+
+use crate::{
+    content::traits::{Container, WidgetOutput},
+    display::{highlight, Item, Line, SelectedText},
+    key,
+    service::{
+        insert_mode::{InsertAction, InsertMode},
+        theme::theme,
+    },
+    traits::{EventHandler, SelectedIndex},
+};
+
+use super::{frame, OverlayWidget};
+
+/// an item of a fuzzy list
+#[derive(Debug, Clone)]
+pub struct FuzzyItem<T> {
+    /// what the input is matched against
+    pub text: String,
+    /// shown dimmed after the text
+    pub detail: String,
+    pub payload: T,
+}
+
+pub enum FuzzyAction<T> {
+    None,
+    Cancel,
+    /// enter was pressed, with what was typed and the selected item if anything matches
+    Accept {
+        text: String,
+        picked: Option<T>,
+    },
+}
+
+/// an input over a list of items, fuzzy filtered by what is typed
+#[derive(Debug)]
+pub struct FuzzyList<T> {
+    /// goes before the input in the title
+    prompt: &'static str,
+    /// in percent of the screen
+    width: u16,
+    /// the most items shown at once
+    rows: u16,
+    /// the part of the input that is matched
+    pattern: fn(&str) -> &str,
+    input: InsertMode,
+    items: Vec<FuzzyItem<T>>,
+    /// index into items and the positions of the matched chars, best match first
+    container: Container<(usize, Vec<usize>)>,
+}
+
+impl<T: Clone> FuzzyList<T> {
+    pub fn new(prompt: &'static str, width: u16, rows: u16, items: Vec<FuzzyItem<T>>) -> Self {
+        let mut input = InsertMode::default();
+        input.listen();
+        let mut l = Self {
+            prompt,
+            width,
+            rows,
+            pattern: str::trim,
+            input,
+            items,
+            container: Default::default(),
+        };
+        l.filter();
+        l
+    }
+
+    /// matches only the part of the input the function gives, the whole trimmed input otherwise
+    pub fn pattern(mut self, pattern: fn(&str) -> &str) -> Self {
+        self.pattern = pattern;
+        self.filter();
+        self
+    }
+
+    pub fn selected(&self) -> Option<&T> {
+        self.container
+            .items
+            .get(self.container.selected_index.selected_index())
+            .map(|(i, _)| &self.items[*i].payload)
+    }
+
+    pub fn replace_input(&mut self, text: String) {
+        self.input.replace_text(text.into());
+        self.filter();
+    }
+
+    fn filter(&mut self) {
+        let text = self.input.text();
+        let pattern = (self.pattern)(&text);
+        let matcher = SkimMatcherV2::default();
+        let mut matches = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| {
+                let (score, indices) = matcher.fuzzy_indices(&item.text, pattern)?;
+                Some((score, i, indices))
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|(score, i, _)| (Reverse(*score), *i));
+        self.container.items = matches.into_iter().map(|(_, i, m)| (i, m)).collect();
+        self.container.selected_index.reset();
+    }
+}
+
+impl<T> OverlayWidget for FuzzyList<T> {
+    fn width(&self) -> u16 {
+        self.width
+    }
+
+    fn height(&self) -> u16 {
+        (self.container.items.len() as u16).clamp(1, self.rows) + 2
+    }
+
+    fn selected_index_mut(&mut self) -> &mut SelectedIndex {
+        &mut self.container.selected_index
+    }
+
+    fn display(&self) -> WidgetOutput<'static> {
+        let mut title = vec![Span::raw(self.prompt)];
+        title.extend(self.input.line().main_text.0);
+        let mut content = frame(Spans::from(title));
+        let st = theme().text;
+        let matched = theme().matched;
+        let line = |item: &FuzzyItem<T>, indices: &[usize], selected: Style| {
+            let mut spans = highlight(
+                &item.text,
+                indices,
+                st.patch(selected),
+                matched.patch(selected),
+            );
+            if !item.detail.is_empty() {
+                spans.0.push(Span::styled(item.detail.clone(), theme().dim));
+            }
+            Line::new(spans)
+        };
+        content.items = self
+            .container
+            .items
+            .iter()
+            .map(|(i, indices)| {
+                let item = &self.items[*i];
+                Item {
+                    text: vec![line(item, indices, Style::default())],
+                    selected_text: SelectedText::Lines(vec![line(item, indices, theme().selected)]),
+                }
+            })
+            .collect();
+        WidgetOutput::MainProvider { content }
+    }
+}
+
+impl<'a, T: Clone> EventHandler<'a> for FuzzyList<T> {
+    type Action = FuzzyAction<T>;
+    type Context = ();
+    fn handle_events(&mut self, event: &Event, _ctx: Self::Context) -> Self::Action {
+        if let Event::Key(key!(Up)) | Event::Key(key!(Down)) = event {
+            self.container.handle_events(event, ());
+            return FuzzyAction::None;
+        }
+        match self.input.handle_events(event, ()) {
+            InsertAction::Rejected(_) => FuzzyAction::Cancel,
+            InsertAction::Accepted { text, .. } => FuzzyAction::Accept {
+                picked: self.selected().cloned(),
+                text,
+            },
+            InsertAction::Action(_) => {
+                self.filter();
+                FuzzyAction::None
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use anyhow::Result;
+use crossterm::event::Event;
+use tui::text::Span;
+
+use crate::{
+    content::traits::{Container, WidgetOutput},
+    key,
+    service::{config::profile, journal},
+    traits::{EventAction, EventHandler, SelectedIndex},
+};
+
+use super::{frame, plain_item, OverlayWidget};
+
+/// what was changed in the last few days
+#[derive(Debug)]
+pub struct History {
+    days: i64,
+    container: Container<String>,
+}
+
+impl History {
+    pub fn new(days: i64) -> Result<Self> {
+        Ok(Self {
+            days,
+            container: Container {
+                items: journal::recent(&profile().db_path, days)?
+                    .iter()
+                    .map(journal::Entry::line)
+                    .collect(),
+                ..Default::default()
+            },
+        })
+    }
+}
+
+impl OverlayWidget for History {
+    fn width(&self) -> u16 {
+        80
+    }
+
+    fn height(&self) -> u16 {
+        (self.container.items.len() as u16).saturating_add(2)
+    }
+
+    fn selected_index_mut(&mut self) -> &mut SelectedIndex {
+        &mut self.container.selected_index
+    }
+
+    fn display(&self) -> WidgetOutput<'static> {
+        let mut content =
+            frame(Span::raw(format!("History of the last {} days", self.days)).into());
+        content.set_numbered(true);
+        content.items = self
+            .container
+            .items
+            .iter()
+            .cloned()
+            .map(plain_item)
+            .collect();
+        WidgetOutput::MainProvider { content }
+    }
+}
+
+impl<'a> EventHandler<'a> for History {
+    /// true if it should be closed
+    type Action = bool;
+    type Context = ();
+    fn handle_events(&mut self, event: &Event, _ctx: Self::Context) -> Self::Action {
+        if let EventAction::Absorbed(_) = self.container.handle_events(event, ()) {
+            return false;
+        }
+        matches!(
+            event,
+            Event::Key(key!(Esc)) | Event::Key(key!('q')) | Event::Key(key!(Enter))
+        )
+    }
+}
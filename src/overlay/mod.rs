@@ -0,0 +1,117 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear},
+    Frame,
+};
+
+use crate::{
+    content::traits::{DrawContext, WidgetOutput},
+    display::{Item, Line, ListBuilder, SelectedText},
+    service::theme::theme,
+    traits::{Drawable, SelectedIndex},
+};
+
+pub mod fuzzy;
+pub mod history;
+pub mod palette;
+pub mod profile_picker;
+pub mod prompt;
+pub mod search;
+
+use history::History;
+use palette::Palette;
+use profile_picker::ProfilePicker;
+use prompt::Prompt;
+use search::Search;
+
+/// popups drawn over the current widget. they get the events before anything else
+#[derive(Debug)]
+pub enum Overlay {
+    ProfilePicker(ProfilePicker),
+    /// the db file was changed by someone else. quitting is set if the prompt came up on quit
+    ExternalChange {
+        prompt: Prompt,
+        quitting: bool,
+    },
+    /// a message that only needs to be dismissed
+    Notice(Prompt),
+    History(History),
+    Palette(Palette),
+    Search(Search),
+}
+
+/// what every overlay has, to draw it and to click on it
+pub trait OverlayWidget {
+    /// in percent of the screen
+    fn width(&self) -> u16;
+    fn height(&self) -> u16;
+    fn display(&self) -> WidgetOutput<'static>;
+    fn selected_index_mut(&mut self) -> &mut SelectedIndex;
+}
+
+impl Overlay {
+    fn widget(&mut self) -> &mut dyn OverlayWidget {
+        match self {
+            Self::ProfilePicker(p) => p,
+            Self::History(h) => h,
+            Self::Palette(p) => &mut p.list,
+            Self::Search(s) => &mut s.list,
+            Self::ExternalChange { prompt: p, .. } | Self::Notice(p) => p,
+        }
+    }
+
+    pub fn selected_index_mut(&mut self) -> &mut SelectedIndex {
+        self.widget().selected_index_mut()
+    }
+
+    pub fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let w = self.widget();
+        let area = centered_rect(w.width(), w.height(), area);
+        f.render_widget(Clear, area);
+        w.display().draw(
+            f,
+            DrawContext {
+                area,
+                selected_index: w.selected_index_mut(),
+            },
+        );
+    }
+}
+
+/// the bordered list an overlay is shown in
+pub fn frame(title: Spans<'static>) -> ListBuilder<'static> {
+    let mut content = ListBuilder::default();
+    content.title(title).block(
+        Block::default()
+            .border_style(theme().border)
+            .borders(Borders::all()),
+    );
+    content
+}
+
+/// a line of text in the theme's text style
+pub fn plain_item(text: String) -> Item<'static> {
+    let st = theme().text;
+    let mut line = Line::new(Span::raw(text));
+    line.text_style(st);
+    Item {
+        text: vec![line],
+        selected_text: SelectedText::Style(st.patch(theme().selected)),
+    }
+}
+
+pub fn centered_rect(percent_x: u16, height: u16, r: Rect) -> Rect {
+    let width = r.width * percent_x.min(100) / 100;
+    let height = height.min(r.height);
+    Rect {
+        x: r.x + (r.width - width) / 2,
+        y: r.y + (r.height - height) / 2,
+        width,
+        height,
+    }
+}
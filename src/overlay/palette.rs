@@ -0,0 +1,78 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use crossterm::event::Event;
+
+use crate::{
+    key,
+    traits::{CommandInfo, EventHandler},
+};
+
+use super::fuzzy::{FuzzyAction, FuzzyItem, FuzzyList};
+
+pub enum PaletteAction {
+    None,
+    Cancel,
+    /// `name args`, as typed or picked
+    Run(String),
+}
+
+/// the commands of the widget in view and the global ones, fuzzy filtered by the typed name
+#[derive(Debug)]
+pub struct Palette {
+    pub list: FuzzyList<CommandInfo>,
+}
+
+impl Palette {
+    pub fn new(commands: Vec<CommandInfo>) -> Self {
+        let items = commands
+            .into_iter()
+            .map(|c| {
+                let mut detail = String::new();
+                if !c.args.is_empty() {
+                    detail.push_str(&format!(" {}", c.args));
+                }
+                detail.push_str(&format!("  {}", c.description));
+                FuzzyItem {
+                    text: c.name.to_owned(),
+                    detail,
+                    payload: c,
+                }
+            })
+            .collect();
+        let list = FuzzyList::new(":", 60, 12, items).pattern(|text| {
+            text.trim_start_matches(':')
+                .split_whitespace()
+                .next()
+                .unwrap_or("")
+        });
+        Self { list }
+    }
+}
+
+/// a single word runs the picked command, so a few letters are enough. with arguments it runs as typed
+fn line(text: &str, picked: Option<CommandInfo>) -> String {
+    let text = text.trim().trim_start_matches(':');
+    match picked {
+        Some(c) if !text.contains(' ') => c.name.to_owned(),
+        _ => text.to_owned(),
+    }
+}
+
+impl<'a> EventHandler<'a> for Palette {
+    type Action = PaletteAction;
+    type Context = ();
+    fn handle_events(&mut self, event: &Event, _ctx: Self::Context) -> Self::Action {
+        if let Event::Key(key!(Tab)) = event {
+            if let Some(name) = self.list.selected().map(|c| c.name) {
+                self.list.replace_input(format!("{name} "));
+            }
+            return PaletteAction::None;
+        }
+        match self.list.handle_events(event, ()) {
+            FuzzyAction::None => PaletteAction::None,
+            FuzzyAction::Cancel => PaletteAction::Cancel,
+            FuzzyAction::Accept { text, picked } => PaletteAction::Run(line(&text, picked)),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use crossterm::event::Event;
+use tui::text::Span;
+
+use crate::{
+    content::traits::{Container, WidgetOutput},
+    display::{Marker, MarkerPos},
+    key,
+    service::{
+        config::{config, profile},
+        theme::theme,
+    },
+    traits::{EventAction, EventHandler, SelectedIndex},
+};
+
+use super::{frame, plain_item, OverlayWidget};
+
+pub enum PickerAction {
+    None,
+    Cancel,
+    Pick(String),
+}
+
+#[derive(Debug)]
+pub struct ProfilePicker {
+    container: Container<String>,
+}
+
+impl ProfilePicker {
+    pub fn new() -> Self {
+        let mut container = Container {
+            items: config().profiles.keys().cloned().collect::<Vec<_>>(),
+            ..Default::default()
+        };
+        let current = container
+            .items
+            .iter()
+            .position(|name| name == &profile().name)
+            .unwrap_or(0);
+        container.selected_index.select(current);
+        Self { container }
+    }
+}
+
+impl OverlayWidget for ProfilePicker {
+    fn width(&self) -> u16 {
+        40
+    }
+
+    fn height(&self) -> u16 {
+        self.container.items.len() as u16 + 2
+    }
+
+    fn selected_index_mut(&mut self) -> &mut SelectedIndex {
+        &mut self.container.selected_index
+    }
+
+    fn display(&self) -> WidgetOutput<'static> {
+        let current = &profile().name;
+        let mut content = frame(Span::raw("Profiles").into());
+        content.items = self
+            .container
+            .items
+            .iter()
+            .map(|name| {
+                let mut item = plain_item(name.clone());
+                item.text[0].markers.push(Marker {
+                    symbol: Span::styled(if name == current { "*" } else { " " }, theme().text),
+                    pos: MarkerPos::Left,
+                });
+                item
+            })
+            .collect();
+        WidgetOutput::MainProvider { content }
+    }
+}
+
+impl<'a> EventHandler<'a> for ProfilePicker {
+    type Action = PickerAction;
+    type Context = ();
+    fn handle_events(&mut self, event: &Event, _ctx: Self::Context) -> Self::Action {
+        if let EventAction::Absorbed(_) = self.container.handle_events(event, ()) {
+            return PickerAction::None;
+        }
+        match event {
+            Event::Key(key!(Enter)) => PickerAction::Pick(
+                self.container.items[self.container.selected_index.selected_index()].clone(),
+            ),
+            Event::Key(key!(Esc)) | Event::Key(key!('q')) => PickerAction::Cancel,
+            _ => PickerAction::None,
+        }
+    }
+}
@@ -0,0 +1,85 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use tui::text::Span;
+
+use crate::{
+    content::traits::{Container, WidgetOutput},
+    key,
+    traits::{EventAction, EventHandler, SelectedIndex},
+};
+
+use super::{frame, plain_item, OverlayWidget};
+
+pub enum PromptAction {
+    None,
+    Cancel,
+    Pick(char),
+}
+
+/// a question with a few single key answers
+#[derive(Debug)]
+pub struct Prompt {
+    title: String,
+    container: Container<(char, &'static str)>,
+}
+
+impl Prompt {
+    pub fn new(title: impl Into<String>, options: Vec<(char, &'static str)>) -> Self {
+        Self {
+            title: title.into(),
+            container: Container {
+                items: options,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl OverlayWidget for Prompt {
+    fn width(&self) -> u16 {
+        50
+    }
+
+    fn height(&self) -> u16 {
+        self.container.items.len() as u16 + 2
+    }
+
+    fn selected_index_mut(&mut self) -> &mut SelectedIndex {
+        &mut self.container.selected_index
+    }
+
+    fn display(&self) -> WidgetOutput<'static> {
+        let mut content = frame(Span::raw(self.title.clone()).into());
+        content.items = self
+            .container
+            .items
+            .iter()
+            .map(|(key, text)| plain_item(format!("[{key}] {text}")))
+            .collect();
+        WidgetOutput::MainProvider { content }
+    }
+}
+
+impl<'a> EventHandler<'a> for Prompt {
+    type Action = PromptAction;
+    type Context = ();
+    fn handle_events(&mut self, event: &Event, _ctx: Self::Context) -> Self::Action {
+        if let EventAction::Absorbed(_) = self.container.handle_events(event, ()) {
+            return PromptAction::None;
+        }
+        match event {
+            Event::Key(key!(Enter)) => PromptAction::Pick(
+                self.container.items[self.container.selected_index.selected_index()].0,
+            ),
+            Event::Key(key!(Esc)) => PromptAction::Cancel,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(c),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) if self.container.items.iter().any(|(k, _)| k == c) => PromptAction::Pick(*c),
+            _ => PromptAction::None,
+        }
+    }
+}
@@ -0,0 +1,54 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use crossterm::event::Event;
+
+use crate::{service::tree::Found, traits::EventHandler};
+
+use super::fuzzy::{FuzzyAction, FuzzyItem, FuzzyList};
+
+pub enum SearchAction {
+    None,
+    Cancel,
+    Pick(Found),
+}
+
+/// fuzzy search over the texts of every list and todo
+#[derive(Debug)]
+pub struct Search {
+    pub list: FuzzyList<Found>,
+}
+
+impl Search {
+    pub fn new(found: Vec<Found>) -> Self {
+        let items = found
+            .into_iter()
+            .map(|f| FuzzyItem {
+                text: f.text.clone(),
+                detail: match f.context.is_empty() {
+                    true => String::new(),
+                    false => format!("  {}", f.context),
+                },
+                payload: f,
+            })
+            .collect();
+        Self {
+            list: FuzzyList::new("search: ", 80, 15, items),
+        }
+    }
+}
+
+impl<'a> EventHandler<'a> for Search {
+    type Action = SearchAction;
+    type Context = ();
+    fn handle_events(&mut self, event: &Event, _ctx: Self::Context) -> Self::Action {
+        match self.list.handle_events(event, ()) {
+            FuzzyAction::None => SearchAction::None,
+            FuzzyAction::Accept {
+                picked: Some(found),
+                ..
+            } => SearchAction::Pick(found),
+            FuzzyAction::Cancel | FuzzyAction::Accept { .. } => SearchAction::Cancel,
+        }
+    }
+}
@@ -3,6 +3,7 @@ use crate::{dbg, debug, error};
 
 use derivative::Derivative;
 use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
 use std::{collections::HashMap, fmt::Debug, marker::PhantomData};
 
 use crate::content::traits::Content;
@@ -22,21 +23,33 @@ macro_rules! to_from_content_id {
     };
 }
 
-#[derive(Derivative, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Derivative, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Id(ContentID<Content>);
 impl Id {
     fn from_id(id: ContentID<Content>) -> Self {
         Self(id)
     }
+
+    /// (id, generation)
+    pub fn raw(&self) -> (u64, u64) {
+        (self.0.id, self.0.generation)
+    }
 }
 to_from_content_id!(Id, Content);
 
 #[derive(Derivative, Serialize, Deserialize, Clone)]
 #[derivative(Debug)]
 pub struct ContentRegister<T, P> {
+    #[serde(with = "entries")]
+    #[serde(bound(serialize = "T: Serialize", deserialize = "T: Deserialize<'de>"))]
     items: HashMap<ContentID<T>, ContentEntry<T>>,
     generation: u64,
 
+    /// items changed since the last take_touched, with their entry (serialized) from before the first change
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    #[derivative(Debug = "ignore")]
+    touched: HashMap<ContentID<T>, Option<Value>>,
+
     #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
     #[derivative(Debug = "ignore")]
     _phantom: PhantomData<P>,
@@ -45,16 +58,37 @@ pub struct ContentRegister<T, P> {
 impl<T, P> ContentRegister<T, P>
 where
     P: From<ContentID<T>> + Into<ContentID<T>>,
-    T: Debug,
+    T: Debug + Serialize,
 {
     pub fn new() -> Self {
         Self {
             items: Default::default(),
             generation: 0,
+            touched: Default::default(),
             _phantom: PhantomData,
         }
     }
 
+    /// keeps the entry as it is before it changes, once until the next take_touched
+    fn touch(&mut self, id: ContentID<T>) {
+        if !self.touched.contains_key(&id) {
+            let before = self.items.get(&id).and_then(|e| entries::value(&id, e));
+            self.touched.insert(id, before);
+        }
+    }
+
+    /// (id, entry before, entry now) of the items that could have changed since the last call, serialized.
+    /// None where the item was not there
+    pub fn take_touched(&mut self) -> Vec<(P, Option<Value>, Option<Value>)> {
+        std::mem::take(&mut self.touched)
+            .into_iter()
+            .map(|(id, before)| {
+                let now = self.items.get(&id).and_then(|e| entries::value(&id, e));
+                (id.into(), before, now)
+            })
+            .collect()
+    }
+
     fn dealloc(&mut self, id: P) -> Option<T> {
         let id: ContentID<T> = id.into();
         self.items.remove(&id).map(|e| e.val)
@@ -66,10 +100,34 @@ where
     }
 
     pub fn get_mut(&mut self, id: P) -> Option<&mut T> {
+        let id: ContentID<T> = id.into();
+        if self.items.contains_key(&id) {
+            self.touch(id);
+        }
+        self.items.get_mut(&id).map(|e| &mut e.val)
+    }
+
+    /// for what is never saved, like the selection or the scroll position. unlike get_mut the item is not
+    /// snapshotted, so changes to what is saved would not be journaled
+    pub fn view_mut(&mut self, id: P) -> Option<&mut T> {
         let id: ContentID<T> = id.into();
         self.items.get_mut(&id).map(|e| &mut e.val)
     }
 
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// the item with its reference count
+    pub fn entry(&self, id: P) -> Option<(&T, u32)> {
+        let id: ContentID<T> = id.into();
+        self.items.get(&id).map(|e| (&e.val, e.id_counter))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (P, &T)> {
+        self.items.iter().map(|(&id, e)| (id.into(), &e.val))
+    }
+
     pub fn alloc<I: Into<T>>(&mut self, item: I) -> P {
         let item = item.into();
         let id = self.set(item, self.generation);
@@ -79,6 +137,7 @@ where
 
     pub fn register(&mut self, id: P) {
         let id: ContentID<_> = id.into();
+        self.touch(id);
         self.items
             .get_mut(&id)
             .expect("can't register if it's not there")
@@ -87,6 +146,7 @@ where
 
     pub fn unregister(&mut self, id: P) -> Option<T> {
         let id: ContentID<_> = id.into();
+        self.touch(id);
         let entry = self
             .items
             .get_mut(&id)
@@ -99,6 +159,13 @@ where
         }
     }
 
+    /// takes the item out, however many references it has left
+    pub fn remove(&mut self, id: P) -> Option<T> {
+        let id: ContentID<T> = id.into();
+        self.touch(id);
+        self.dealloc(id.into())
+    }
+
     fn set(&mut self, item: T, id: u64) -> P {
         let entry = ContentEntry {
             val: item,
@@ -110,6 +177,7 @@ where
             generation: self.generation,
             _phantom: PhantomData,
         };
+        self.touch(id);
         self.items.insert(id, entry);
         id.into()
     }
@@ -126,6 +194,83 @@ where
     // }
 }
 
+/// the items are stored as a list of entries (sorted by id), as not all formats support non string keys in maps
+mod entries {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    use super::{ContentEntry, ContentID};
+
+    #[derive(Serialize)]
+    struct EntryRef<'a, T> {
+        id: &'a ContentID<T>,
+        val: &'a T,
+        generation: u64,
+        id_counter: u32,
+    }
+
+    /// the entry as it is serialized in the register
+    pub fn value<T: Serialize>(
+        id: &ContentID<T>,
+        e: &ContentEntry<T>,
+    ) -> Option<serde_yaml::Value> {
+        serde_yaml::to_value(EntryRef {
+            id,
+            val: &e.val,
+            generation: e.generation,
+            id_counter: e.id_counter,
+        })
+        .ok()
+    }
+
+    #[derive(Deserialize)]
+    struct Entry<T> {
+        id: ContentID<T>,
+        val: T,
+        generation: u64,
+        id_counter: u32,
+    }
+
+    pub fn serialize<S, T>(
+        items: &HashMap<ContentID<T>, ContentEntry<T>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        let mut entries = items.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(id, _)| (id.id, id.generation));
+        serializer.collect_seq(entries.into_iter().map(|(id, e)| EntryRef {
+            id,
+            val: &e.val,
+            generation: e.generation,
+            id_counter: e.id_counter,
+        }))
+    }
+
+    pub fn deserialize<'de, D, T>(
+        deserializer: D,
+    ) -> Result<HashMap<ContentID<T>, ContentEntry<T>>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        let entries = Vec::<Entry<T>>::deserialize(deserializer)?;
+        Ok(entries
+            .into_iter()
+            .map(|e| {
+                let entry = ContentEntry {
+                    val: e.val,
+                    generation: e.generation,
+                    id_counter: e.id_counter,
+                };
+                (e.id, entry)
+            })
+            .collect())
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct ContentEntry<T> {
     val: T,
@@ -160,3 +305,20 @@ impl<T> PartialEq for ContentID<T> {
     }
 }
 impl<T> Eq for ContentID<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::todo::Todo;
+
+    #[test]
+    fn only_get_mut_snapshots() {
+        let mut register = ContentRegister::<Content, Id>::new();
+        let id = register.alloc(Todo::new("a"));
+        register.take_touched();
+        register.view_mut(id);
+        assert!(register.take_touched().is_empty());
+        register.get_mut(id);
+        assert_eq!(register.take_touched().len(), 1);
+    }
+}
@@ -3,7 +3,7 @@ use crate::{dbg, debug, error};
 
 use anyhow::Result;
 use crossterm::{
-    event::DisableMouseCapture,
+    event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -16,10 +16,28 @@ use tui::{
     Terminal,
 };
 
-use crate::{app::App, service::log::init_logger};
+use crate::{
+    app::App,
+    cli::{self, Command},
+    service::{config::set_profile, lock, log::init_logger, remind},
+};
 
 pub async fn run() -> Result<()> {
-    init_logger().expect("failed to init logger");
+    // commands without the tui don't log. the log file is relative to the checkout and belongs to the app,
+    // a merge driver runs anywhere and maybe next to a running app
+    let (profile, args) = cli::take_profile(std::env::args().skip(1).collect())?;
+    if let Some(name) = profile {
+        set_profile(&name)?;
+    }
+    match Command::parse(args.into_iter())? {
+        Command::Tui => init_logger().expect("failed to init logger"),
+        Command::Remind { daemon: true } => return remind::daemon().await,
+        cmd => {
+            let res = cmd.run();
+            lock::release_all();
+            return res;
+        }
+    }
 
     // yt_manager::test().unwrap();
     // return Ok(());
@@ -32,14 +50,14 @@ pub async fn run() -> Result<()> {
 
         // restore terminal
         let _ = restore_terminal(&mut terminal); // ignore errors in panic hook
+        lock::release_all();
         hook(info)
     }));
 
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    // execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
@@ -48,7 +66,9 @@ pub async fn run() -> Result<()> {
     app.run(&mut terminal).await.unwrap();
 
     restore_terminal(&mut terminal)?;
-    app.save()?;
+    let res = app.save();
+    lock::release_all();
+    res?;
 
     Ok(())
 }
@@ -1,12 +1,15 @@
 #[allow(unused_imports)]
 use crate::{dbg, debug, error};
 
+use anyhow::{bail, Result};
 use dirs;
-use once_cell::sync::OnceCell;
+use once_cell::sync::{Lazy, OnceCell};
 use serde::{Deserialize, Serialize};
-use std::{io::Read, path::PathBuf};
+use std::{collections::BTreeMap, io::Read, path::PathBuf, sync::RwLock};
 use toml;
 
+use super::theme::{ColorSupport, Theme};
+
 pub fn config() -> &'static Config {
     static CONFIG: OnceCell<Config> = OnceCell::new();
     CONFIG.get_or_init(|| {
@@ -24,31 +27,312 @@ pub fn config() -> &'static Config {
     })
 }
 
+static ACTIVE_PROFILE: Lazy<RwLock<Option<String>>> = Lazy::new(Default::default);
+
+/// the profile in use. the default profile unless another one is selected with set_profile
+pub fn profile() -> &'static Profile {
+    let config = config();
+    ACTIVE_PROFILE
+        .read()
+        .unwrap()
+        .as_ref()
+        .and_then(|name| config.profiles.get(name))
+        .unwrap_or(&config.profiles[&config.default_profile])
+}
+
+pub fn set_profile(name: &str) -> Result<()> {
+    if !config().profiles.contains_key(name) {
+        bail!("no profile named {name}");
+    }
+    *ACTIVE_PROFILE.write().unwrap() = Some(name.to_owned());
+    Ok(())
+}
+
 type MaybeString = Option<String>;
 type MaybePath = Option<PathBuf>;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
-struct ConfigBuilder {
+struct ProfileBuilder {
     db_path: MaybePath,
+    theme: MaybeString,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct Config {
+struct ConfigBuilder {
+    db_path: MaybePath, // db of the "default" profile
+    theme: MaybeString, // theme of the "default" profile
+    default_profile: MaybeString,
+    profiles: Option<BTreeMap<String, ProfileBuilder>>,
+    notify_command: MaybeString,
+    remind_before: Option<Vec<u32>>,
+    pomodoro: Option<PomodoroBuilder>,
+    search: Option<SearchBuilder>,
+    due: Option<DueBuilder>,
+    long_lines: MaybeString,
+    line_numbers: MaybeString,
+    scrolloff: Option<usize>,
+    colors: MaybeString,
+    themes: Option<BTreeMap<String, BTreeMap<String, String>>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct SearchBuilder {
+    regex: Option<bool>,
+    ignore_case: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct DueBuilder {
+    soon_days: Option<u32>,
+    far_days: Option<u32>,
+    relative: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PomodoroBuilder {
+    work: Option<u32>,
+    short_break: Option<u32>,
+    long_break: Option<u32>,
+    long_break_every: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Profile {
+    pub name: String,
     pub db_path: PathBuf, // TODO: have a general config path and have this relative to that
+    pub theme: Option<String>,
 }
-impl Default for Config {
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Config {
+    pub default_profile: String,
+    pub profiles: BTreeMap<String, Profile>, // always has a "default" profile
+    /// program and args, split on whitespace and run without a shell. {title} and {body} are replaced
+    pub notify_command: Option<Vec<String>>,
+    /// minutes before a todo is due to remind of it, longest first. 0 is when it is due
+    pub remind_before: Vec<u32>,
+    pub pomodoro: Pomodoro,
+    pub search: Search,
+    pub due: Due,
+    pub long_lines: LongLines,
+    pub line_numbers: LineNumbers,
+    /// items kept in view above and below the selected one
+    pub scrolloff: usize,
+    /// how many colors the terminal shows, the colors of the themes are fitted to it
+    #[serde(skip)]
+    pub colors: ColorSupport,
+    /// the built-in dark and light themes and the ones from the config
+    #[serde(skip)]
+    pub themes: BTreeMap<String, Theme>,
+}
+
+/// how / in a list matches at first. both can be toggled while typing
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Search {
+    pub regex: bool,
+    pub ignore_case: bool,
+}
+impl Default for Search {
+    fn default() -> Self {
+        Self {
+            regex: false,
+            ignore_case: true,
+        }
+    }
+}
+
+/// what happens to text wider than its list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum LongLines {
+    Wrap,
+    /// cut off with …
+    Truncate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum LineNumbers {
+    Off,
+    Absolute,
+    /// distance to the selected item, which shows its own number
+    Relative,
+}
+
+/// how due dates are shown
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Due {
+    /// due within this many days is soon
+    pub soon_days: u32,
+    /// due after this many days is far off and dimmed
+    pub far_days: u32,
+    /// in 3d or 2w ago instead of dd-mm-yyyy
+    pub relative: bool,
+}
+impl Default for Due {
     fn default() -> Self {
         Self {
+            soon_days: 3,
+            far_days: 30,
+            relative: false,
+        }
+    }
+}
+
+/// lengths in minutes
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Pomodoro {
+    pub work: u32,
+    pub short_break: u32,
+    pub long_break: u32,
+    /// work sessions before a long break
+    pub long_break_every: u32,
+}
+impl Default for Pomodoro {
+    fn default() -> Self {
+        Self {
+            work: 25,
+            short_break: 5,
+            long_break: 15,
+            long_break_every: 4,
+        }
+    }
+}
+impl Default for Config {
+    fn default() -> Self {
+        let default = Profile {
+            name: "default".into(),
             db_path: dirs::config_dir().unwrap().join("wildo/db.yaml"),
+            theme: None,
+        };
+        Self {
+            default_profile: default.name.clone(),
+            profiles: [(default.name.clone(), default)].into_iter().collect(),
+            notify_command: None,
+            remind_before: vec![15, 0],
+            pomodoro: Default::default(),
+            search: Default::default(),
+            due: Default::default(),
+            long_lines: LongLines::Wrap,
+            line_numbers: LineNumbers::Off,
+            scrolloff: 0,
+            colors: ColorSupport::detect(),
+            themes: [("dark", Theme::dark()), ("light", Theme::light())]
+                .into_iter()
+                .map(|(name, theme)| (name.to_owned(), theme))
+                .collect(),
         }
     }
 }
 
 impl From<ConfigBuilder> for Config {
     fn from(cb: ConfigBuilder) -> Self {
+        let mut def = Self::default();
+        if let Some(path) = cb.db_path {
+            def.profiles.get_mut("default").unwrap().db_path = expand_path(path);
+        }
+        def.profiles.get_mut("default").unwrap().theme = cb.theme;
+        for (name, p) in cb.profiles.unwrap_or_default() {
+            let profile = Profile {
+                db_path: p.db_path.map(expand_path).unwrap_or(
+                    dirs::config_dir()
+                        .unwrap()
+                        .join(format!("wildo/{name}.yaml")),
+                ),
+                theme: p.theme,
+                name: name.clone(),
+            };
+            def.profiles.insert(name, profile);
+        }
+        let mut themes = def.themes;
+        for (name, table) in cb.themes.unwrap_or_default() {
+            let theme = Theme::build(&name, &table, &themes);
+            themes.insert(name, theme);
+        }
+        for p in def.profiles.values() {
+            if let Some(name) = p.theme.as_ref().filter(|name| !themes.contains_key(*name)) {
+                error!("profile {}: no theme named {name}", p.name);
+            }
+        }
+        let long_lines = match cb.long_lines.as_deref() {
+            Some("wrap") => LongLines::Wrap,
+            Some("truncate") => LongLines::Truncate,
+            Some(l) => {
+                error!("long_lines should be wrap or truncate, not {l}");
+                def.long_lines
+            }
+            None => def.long_lines,
+        };
+        let line_numbers = match cb.line_numbers.as_deref() {
+            Some("off") => LineNumbers::Off,
+            Some("absolute") => LineNumbers::Absolute,
+            Some("relative") => LineNumbers::Relative,
+            Some(n) => {
+                error!("line_numbers should be off, absolute or relative, not {n}");
+                def.line_numbers
+            }
+            None => def.line_numbers,
+        };
+        let colors = match cb.colors {
+            Some(c) => ColorSupport::parse(&c).unwrap_or_else(|| {
+                error!("colors should be truecolor, 256 or 16, not {c}");
+                def.colors
+            }),
+            None => def.colors,
+        };
+        let themes = themes
+            .into_iter()
+            .map(|(name, theme)| (name, theme.fit(colors)))
+            .collect();
+        Self {
+            default_profile: cb
+                .default_profile
+                .filter(|name| def.profiles.contains_key(name))
+                .unwrap_or(def.default_profile),
+            profiles: def.profiles,
+            notify_command: cb
+                .notify_command
+                .map(|c| c.split_whitespace().map(str::to_owned).collect::<Vec<_>>())
+                .filter(|c| !c.is_empty()),
+            remind_before: cb
+                .remind_before
+                .map(|mut r| {
+                    r.sort_unstable_by(|a, b| b.cmp(a));
+                    r.dedup();
+                    r
+                })
+                .unwrap_or(def.remind_before),
+            pomodoro: cb.pomodoro.map(Pomodoro::from).unwrap_or_default(),
+            search: cb
+                .search
+                .map(|sb| Search {
+                    regex: sb.regex.unwrap_or(def.search.regex),
+                    ignore_case: sb.ignore_case.unwrap_or(def.search.ignore_case),
+                })
+                .unwrap_or(def.search),
+            due: cb
+                .due
+                .map(|db| Due {
+                    soon_days: db.soon_days.unwrap_or(def.due.soon_days),
+                    far_days: db.far_days.unwrap_or(def.due.far_days),
+                    relative: db.relative.unwrap_or(def.due.relative),
+                })
+                .unwrap_or(def.due),
+            long_lines,
+            line_numbers,
+            scrolloff: cb.scrolloff.unwrap_or(def.scrolloff),
+            colors,
+            themes,
+        }
+    }
+}
+
+impl From<PomodoroBuilder> for Pomodoro {
+    fn from(pb: PomodoroBuilder) -> Self {
         let def = Self::default();
         Self {
-            db_path: cb.db_path.map(expand_path).unwrap_or(def.db_path),
+            work: pb.work.unwrap_or(def.work).max(1),
+            short_break: pb.short_break.unwrap_or(def.short_break).max(1),
+            long_break: pb.long_break.unwrap_or(def.long_break).max(1),
+            long_break_every: pb.long_break_every.unwrap_or(def.long_break_every).max(1),
         }
     }
 }
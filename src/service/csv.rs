@@ -0,0 +1,57 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use crate::{
+    content::{todo::Todo, todo_list::TodoList, traits::Content},
+    register::{ContentRegister, Id},
+};
+
+/// the tracked time of every todo in the lists (subtasks included), one interval per row.
+/// a running timer has no end yet, its seconds are counted up to now
+pub fn export_intervals(register: &ContentRegister<Content, Id>, lists: &[Id]) -> String {
+    let mut out = "list,todo,start,end,seconds\n".to_owned();
+    for &list in lists {
+        export_list(register, list, &mut out);
+    }
+    out
+}
+
+fn export_list(register: &ContentRegister<Content, Id>, list: Id, out: &mut String) {
+    let content = match register.get(list) {
+        Some(c) => c,
+        None => return,
+    };
+    let title = content.as_display().text();
+    let items = match content.as_any().downcast_ref::<TodoList>() {
+        Some(list) => list.items(),
+        None => return,
+    };
+    for t in items
+        .iter()
+        .filter_map(|&id| register.get(id))
+        .filter_map(|c| c.as_any().downcast_ref::<Todo>())
+    {
+        for i in &t.intervals {
+            out.push_str(&format!(
+                "{},{},{},{},{}\n",
+                field(&title),
+                field(&t.content),
+                i.start.to_rfc3339(),
+                i.end.map(|e| e.to_rfc3339()).unwrap_or_default(),
+                i.duration().num_seconds()
+            ));
+        }
+        if let Some(sub) = t.subtasks {
+            export_list(register, sub, out);
+        }
+    }
+}
+
+/// quotes the field if it has to be
+fn field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
@@ -1,48 +1,130 @@
 #[allow(unused_imports)]
 use crate::{dbg, debug, error};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use serde_yaml;
-use std::{
-    fs::File,
-    io::{BufReader, BufWriter, Read, Write},
-};
+use std::path::Path;
 
 use crate::{
-    content::traits::Content,
+    content::{main_provider::MainProvider, traits::Content},
     register::{ContentRegister, Id},
-    service::config::config,
+    service::{
+        config::profile,
+        journal::{self, Journal},
+        lock, migrations, storage,
+    },
 };
 
 use super::editors::EditManager;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DBFormat {
+    Yaml,
+    Json,
+}
+impl DBFormat {
+    /// json for *.json, yaml for anything else
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::Json,
+            _ => Self::Yaml,
+        }
+    }
+
+    pub fn parse(self, s: &str) -> Result<serde_yaml::Value> {
+        let v = match self {
+            Self::Yaml => serde_yaml::from_str(s)?,
+            Self::Json => serde_json::from_str(s)?,
+        };
+        Ok(v)
+    }
+
+    pub fn to_string<T: Serialize>(self, t: &T) -> Result<String> {
+        let s = match self {
+            Self::Yaml => serde_yaml::to_string(t)?,
+            Self::Json => serde_json::to_string_pretty(t)?,
+        };
+        Ok(s)
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct DBHandler {
+    #[serde(default)]
+    pub schema_version: u32,
     pub register: ContentRegister<Content, Id>,
-    #[serde(skip_serializing, skip_deserializing, default = "Default::default")] // TODO: yet to be implimented properly
+    // TODO: yet to be implimented properly
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
     pub editor: EditManager,
+    /// the register as loaded, so save can journal what changed
+    #[serde(skip_serializing, skip_deserializing, default = "Default::default")]
+    journal: Journal,
 }
 
 impl DBHandler {
+    pub fn new() -> Self {
+        let mut register = ContentRegister::new();
+        let mp: Content = MainProvider::new("Wildo").into();
+        let _ = register.alloc(mp);
+        Self::from_register(register)
+    }
+
+    pub fn from_register(register: ContentRegister<Content, Id>) -> Self {
+        Self {
+            schema_version: migrations::SCHEMA_VERSION,
+            register,
+            editor: Default::default(),
+            journal: Default::default(),
+        }
+    }
+
+    pub fn main_provider(&self) -> Option<Id> {
+        main_provider(&self.register)
+    }
+
+    /// also takes the lock on the db, see lock::holder for whether it can be saved.
+    /// changes journaled since the last save are replayed
     pub fn load() -> Result<Option<Self>> {
-        let db_path = config().db_path.as_path();
-        let file = match File::open(db_path) {
-            Ok(file) => file,
-            Err(_) => return Ok(None), // no problem is file does not exist
+        let path = &profile().db_path;
+        lock::acquire(path)?;
+        let mut db = match Self::load_from(path)? {
+            Some(db) => db,
+            None => return Ok(None),
         };
-        let mut red = BufReader::new(file);
-        let mut buf = String::new();
-        red.read_to_string(&mut buf)?;
-        let dbh = serde_yaml::from_str(&buf)?;
-        Ok(dbh)
+        let (register, replayed) = journal::replay(path, db.register)?;
+        if replayed > 0 {
+            debug!("replayed {replayed} journaled changes");
+        }
+        db.register = register;
+        db.journal = Journal::new(path);
+        db.journal.reset(&mut db.register);
+        Ok(Some(db))
     }
 
-    pub fn save(&self) -> Result<()> {
-        let db_path = config().db_path.as_path();
-        let mut w = BufWriter::new(File::create(db_path)?);
-        let yaml = serde_yaml::to_string(self)?;
-        write!(w, "{yaml}")?;
-        Ok(())
+    pub fn save(&mut self) -> Result<()> {
+        let path = &profile().db_path;
+        if let Some(holder) = lock::holder(path) {
+            bail!("db is locked by {holder}");
+        }
+        self.save_to(path)?;
+        self.journal.set_db_path(path);
+        self.journal.record(&mut self.register)?;
+        self.journal.snapshot()
     }
+
+    pub fn load_from(db_path: &Path) -> Result<Option<Self>> {
+        storage::open(db_path)?.load()
+    }
+
+    pub fn save_to(&self, db_path: &Path) -> Result<()> {
+        storage::open(db_path)?.save(&self.register)
+    }
+}
+
+pub fn main_provider(register: &ContentRegister<Content, Id>) -> Option<Id> {
+    register
+        .iter()
+        .find(|(_, c)| c.as_any().is::<MainProvider>())
+        .map(|(id, _)| id)
 }
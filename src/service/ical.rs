@@ -0,0 +1,410 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::{
+    content::{
+        todo::{Date, Time, Todo, TodoStatus},
+        todo_list::TodoList,
+        traits::Content,
+    },
+    register::{ContentRegister, Id},
+    service::{editors::Yank, tree},
+};
+
+/// exports every dated todo in the given lists as VTODOs. todos without a uid get one made from their id,
+/// the same on every export, so that calendars and importing the file back update them instead of duplicating
+pub fn export(register: &ContentRegister<Content, Id>, lists: &[Id]) -> String {
+    let mut todos = vec![];
+    for &list in lists {
+        collect_dated(register, list, &mut todos);
+    }
+
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//wildo//wildo//EN");
+    for (id, category) in todos {
+        let t = register
+            .get(id)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Todo>()
+            .unwrap();
+        let uid = uid(id, t);
+        push_line(&mut out, "BEGIN:VTODO");
+        push_line(&mut out, &format!("UID:{}", escape(&uid)));
+        push_line(&mut out, &format!("DTSTAMP:{stamp}"));
+        push_line(&mut out, &format!("SUMMARY:{}", escape(&t.content)));
+        let date = NaiveDate::from(t.due_date.unwrap());
+        match &t.due_time {
+            Some(time) => push_line(
+                &mut out,
+                &format!(
+                    "DUE:{}T{:02}{:02}{:02}",
+                    date.format("%Y%m%d"),
+                    time.hour,
+                    time.min,
+                    time.sec
+                ),
+            ),
+            None => push_line(
+                &mut out,
+                &format!("DUE;VALUE=DATE:{}", date.format("%Y%m%d")),
+            ),
+        }
+        let status = match t.status {
+            TodoStatus::Pending => "NEEDS-ACTION",
+            TodoStatus::Done => "COMPLETED",
+            TodoStatus::Ignored => "CANCELLED",
+        };
+        push_line(&mut out, &format!("STATUS:{status}"));
+        push_line(&mut out, &format!("CATEGORIES:{}", escape(&category)));
+        push_line(&mut out, "END:VTODO");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// the uid the todo was imported with, or one made from its id. the creation time keeps todos with the same id
+/// in different dbs apart
+fn uid(id: Id, t: &Todo) -> String {
+    match &t.uid {
+        Some(uid) => uid.clone(),
+        None => {
+            let (i, g) = id.raw();
+            let created = t.created_at.map_or(0, |c| c.timestamp());
+            format!("{i}-{g}-{created}@wildo")
+        }
+    }
+}
+
+/// (todo id, title of the list it is in) for all todos with a due date, subtasks included
+fn collect_dated(register: &ContentRegister<Content, Id>, list: Id, out: &mut Vec<(Id, String)>) {
+    let content = match register.get(list) {
+        Some(c) => c,
+        None => return,
+    };
+    let title = content.as_display().text();
+    let items = match content.as_any().downcast_ref::<TodoList>() {
+        Some(list) => list.items(),
+        None => return,
+    };
+    for &id in items {
+        if let Some(t) = register
+            .get(id)
+            .and_then(|c| c.as_any().downcast_ref::<Todo>())
+        {
+            if t.due_date.is_some() {
+                out.push((id, title.to_string()));
+            }
+            if let Some(sub) = t.subtasks {
+                collect_dated(register, sub, out);
+            }
+        }
+    }
+}
+
+/// imports the VTODOs into the list. todos with a known uid are updated in place (whichever list they are in)
+/// returns the number of (created, updated) todos
+pub fn import(register: &mut ContentRegister<Content, Id>, text: &str, list: Id) -> (usize, usize) {
+    let mut known = tree::reachable_ids(register)
+        .into_iter()
+        .filter_map(|id| {
+            register
+                .get(id)?
+                .as_any()
+                .downcast_ref::<Todo>()
+                .map(|t| (uid(id, t), id))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let (mut created, mut updated) = (0, 0);
+    let mut vtodo: Option<Vec<(String, String, String)>> = None;
+    for line in unfold(text) {
+        match line.as_str() {
+            "BEGIN:VTODO" => vtodo = Some(vec![]),
+            "END:VTODO" => {
+                let props = match vtodo.take() {
+                    Some(props) => props,
+                    None => continue,
+                };
+                let uid = props
+                    .iter()
+                    .find(|(name, _, _)| name == "UID")
+                    .map(|(_, _, v)| unescape(v));
+                match uid.as_ref().and_then(|uid| known.get(uid)) {
+                    Some(&id) => {
+                        let t = register
+                            .get_mut(id)
+                            .unwrap()
+                            .as_any_mut()
+                            .downcast_mut::<Todo>()
+                            .unwrap();
+                        apply(t, &props);
+                        updated += 1;
+                    }
+                    None => {
+                        let mut t = Todo::new("");
+                        t.uid = uid.clone();
+                        apply(&mut t, &props);
+                        let id = register.alloc(t);
+                        // the same uid further down the file updates this one
+                        known.extend(uid.map(|uid| (uid, id)));
+                        let dest = register.get_mut(list).unwrap();
+                        let pos = dest
+                            .as_any()
+                            .downcast_ref::<TodoList>()
+                            .unwrap()
+                            .items()
+                            .len();
+                        dest.as_yankdest().unwrap().insert(Yank { id, pos });
+                        created += 1;
+                    }
+                }
+            }
+            _ => {
+                if let Some(props) = vtodo.as_mut() {
+                    if let Some(prop) = parse_property(&line) {
+                        props.push(prop);
+                    }
+                }
+            }
+        }
+    }
+    (created, updated)
+}
+
+fn apply(t: &mut Todo, props: &[(String, String, String)]) {
+    for (name, params, value) in props {
+        match name.as_str() {
+            "SUMMARY" => t.content = Cow::Owned(unescape(value)),
+            "STATUS" => t.set_status(match value.as_str() {
+                "COMPLETED" => TodoStatus::Done,
+                "CANCELLED" => TodoStatus::Ignored,
+                _ => TodoStatus::Pending,
+            }),
+            "DUE" => {
+                if let Some((date, time)) = parse_due(params, value) {
+                    t.due_date = Some(date);
+                    t.due_time = time;
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// DUE;VALUE=DATE:20221018, DUE:20221018T093000 (floating/TZID, taken as local) or DUE:20221018T093000Z (utc)
+fn parse_due(params: &str, value: &str) -> Option<(Date, Option<Time>)> {
+    if (params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME")) || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((date.into(), None));
+    }
+    let dt = match value.strip_suffix('Z') {
+        Some(value) => {
+            let dt = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+            Utc.from_utc_datetime(&dt)
+                .with_timezone(&Local)
+                .naive_local()
+        }
+        None => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?,
+    };
+    let time = dt.time();
+    Some((
+        dt.date().into(),
+        Some(Time {
+            hour: time.hour() as _,
+            min: time.minute() as _,
+            sec: time.second() as _,
+        }),
+    ))
+}
+
+/// NAME;PARAMS:VALUE -> (name, params, value)
+fn parse_property(line: &str) -> Option<(String, String, String)> {
+    let (head, value) = line.split_once(':')?;
+    let (name, params) = head.split_once(';').unwrap_or((head, ""));
+    Some((name.to_uppercase(), params.to_uppercase(), value.to_owned()))
+}
+
+/// joins the folded lines (continuation lines start with a space or a tab)
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in text.lines() {
+        let line = line.trim_end_matches('\r');
+        match (
+            line.strip_prefix(' ').or(line.strip_prefix('\t')),
+            lines.last_mut(),
+        ) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_owned()),
+        }
+    }
+    lines
+}
+
+/// content lines should not be longer than 75 octets, the rest is folded onto lines starting with a space
+fn push_line(out: &mut String, line: &str) {
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            len = 1;
+        }
+        out.push(c);
+        len += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(c) => out.push(c),
+            None => (),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::db::DBHandler;
+
+    /// a db with a list of the todos in the main provider
+    fn db(todos: Vec<Todo>) -> (ContentRegister<Content, Id>, Id) {
+        let mut register = DBHandler::new().register;
+        let mp = crate::service::db::main_provider(&register).unwrap();
+        let list = register.alloc(TodoList::new("cal"));
+        insert(&mut register, mp, list, 0);
+        for (pos, t) in todos.into_iter().enumerate() {
+            let id = register.alloc(t);
+            insert(&mut register, list, id, pos);
+        }
+        (register, list)
+    }
+
+    fn insert(register: &mut ContentRegister<Content, Id>, dest: Id, id: Id, pos: usize) {
+        let dest = register.get_mut(dest).unwrap();
+        dest.as_yankdest().unwrap().insert(Yank { id, pos });
+    }
+
+    fn todo(content: &'static str, due: (i32, u32, u32), time: Option<Time>) -> Todo {
+        let mut t = Todo::new(content);
+        t.due_date = Some(NaiveDate::from_ymd_opt(due.0, due.1, due.2).unwrap().into());
+        t.due_time = time;
+        t
+    }
+
+    fn todos(register: &ContentRegister<Content, Id>, list: Id) -> Vec<&Todo> {
+        register
+            .get(list)
+            .and_then(|c| c.as_any().downcast_ref::<TodoList>())
+            .unwrap()
+            .items()
+            .iter()
+            .filter_map(|&id| register.get(id)?.as_any().downcast_ref::<Todo>())
+            .collect()
+    }
+
+    #[test]
+    fn imports_what_it_exported() {
+        let mut done = todo(
+            "a long summary, with a comma; and a semicolon that has to be folded onto more lines",
+            (2026, 10, 20),
+            Some(Time {
+                hour: 9,
+                min: 30,
+                sec: 0,
+            }),
+        );
+        done.set_status(TodoStatus::Done);
+        let (register, list) = db(vec![done, todo("pending", (2026, 11, 1), None)]);
+        let text = export(&register, &[list]);
+        assert!(text.lines().all(|l| l.len() <= 75));
+
+        let (mut other, dest) = db(vec![]);
+        assert_eq!(import(&mut other, &text, dest), (2, 0));
+        let imported = todos(&other, dest);
+        let original = todos(&register, list);
+        for (a, b) in imported.iter().zip(&original) {
+            assert_eq!(a.content, b.content);
+            assert_eq!(a.status, b.status);
+            assert_eq!(
+                a.due_date.map(NaiveDate::from),
+                b.due_date.map(NaiveDate::from)
+            );
+            assert_eq!(
+                a.due_time.as_ref().map(|t| (t.hour, t.min)),
+                b.due_time.as_ref().map(|t| (t.hour, t.min))
+            );
+        }
+        // the uids are kept, so the next import updates
+        assert_eq!(
+            export(&other, &[dest]),
+            text.replace(&stamp(&text), &stamp(&text))
+        );
+        assert_eq!(import(&mut other, &text, dest), (0, 2));
+    }
+
+    fn stamp(text: &str) -> String {
+        text.lines()
+            .find(|l| l.starts_with("DTSTAMP"))
+            .unwrap()
+            .to_owned()
+    }
+
+    #[test]
+    fn exports_the_same_uids_every_time() {
+        let (register, list) = db(vec![todo("a", (2026, 10, 20), None)]);
+        let uids = |text: String| {
+            text.lines()
+                .filter(|l| l.starts_with("UID"))
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            uids(export(&register, &[list])),
+            uids(export(&register, &[list]))
+        );
+        // without a uid of its own the todo is still found by the one made from its id
+        let text = export(&register, &[list]);
+        let mut register = register;
+        assert_eq!(import(&mut register, &text, list), (0, 1));
+    }
+
+    #[test]
+    fn a_uid_repeated_in_the_file_updates_the_todo_it_created() {
+        let text = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VTODO\r\nUID:x\r\nSUMMARY:first\r\nDUE;VALUE=DATE:20261101\r\nEND:VTODO\r\n\
+            BEGIN:VTODO\r\nUID:x\r\nSUMMARY:second\r\nSTATUS:COMPLETED\r\nDUE;VALUE=DATE:20261101\r\nEND:VTODO\r\n\
+            END:VCALENDAR\r\n";
+        let (mut register, list) = db(vec![]);
+        assert_eq!(import(&mut register, text, list), (1, 1));
+        let todos = todos(&register, list);
+        assert_eq!(todos.len(), 1);
+        assert_eq!(
+            (todos[0].content.as_ref(), todos[0].status),
+            ("second", TodoStatus::Done)
+        );
+    }
+}
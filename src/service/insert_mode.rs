@@ -13,6 +13,7 @@ use crate::{
     app::AppAction,
     display::Line,
     key,
+    service::theme::theme,
     traits::{EventAction, EventHandler},
 };
 
@@ -36,6 +37,10 @@ impl InsertMode {
         self.listening
     }
 
+    pub fn text(&self) -> String {
+        self.text.iter().collect()
+    }
+
     pub fn replace_text(&mut self, text: Cow<'static, str>) {
         self.text = text.chars().collect();
         self.pos = self.text.len();
@@ -55,7 +60,7 @@ impl InsertMode {
                         .unwrap_or(' ')
                 )
                 .into(),
-                style: Style::default().add_modifier(Modifier::REVERSED),
+                style: theme().cursor,
             },
             Span::raw(self.text.iter().skip(self.pos + 1).collect::<String>()),
         ]))
@@ -0,0 +1,394 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    content::traits::Content,
+    register::{ContentRegister, Id},
+    service::merge::{entries, generation, id_value, key, label, Key},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub at: DateTime<Local>,
+    /// text of the item when it was changed
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub what: String,
+    #[serde(flatten)]
+    pub change: Change,
+}
+
+/// ids are serialized Ids, vals serialized Content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Change {
+    /// the db was saved, everything before this is in it
+    Snapshot,
+    Alloc {
+        id: Value,
+        val: Value,
+    },
+    Unregister {
+        id: Value,
+    },
+    Set {
+        id: Value,
+        field: String,
+        value: Value,
+    },
+    Insert {
+        id: Value,
+        pos: usize,
+        item: Value,
+    },
+    Remove {
+        id: Value,
+        item: Value,
+    },
+}
+
+impl Entry {
+    pub fn line(&self) -> String {
+        format!("{}  {}", self.at.format("%d-%m-%Y %H:%M"), self.describe())
+    }
+
+    pub fn describe(&self) -> String {
+        let what = match self.what.as_str() {
+            "" => "an item".to_owned(),
+            what => format!("\"{what}\""),
+        };
+        match &self.change {
+            Change::Snapshot => "saved".to_owned(),
+            Change::Alloc { .. } => format!("added {what}"),
+            Change::Unregister { .. } => format!("deleted {what}"),
+            Change::Set { field, value, .. } => {
+                format!("set {field} of {what} to {}", short(value))
+            }
+            Change::Insert { .. } => format!("put an item into {what}"),
+            Change::Remove { .. } => format!("took an item out of {what}"),
+        }
+    }
+}
+
+fn short(v: &Value) -> String {
+    match v {
+        Value::Null => "nothing".to_owned(),
+        _ => serde_json::to_string(v).unwrap_or_default(),
+    }
+}
+
+/// how long changes stay in the journal once they are saved
+const KEEP_DAYS: i64 = 30;
+
+/// append only log of the changes to the register, as json lines next to the db
+#[derive(Debug, Default)]
+pub struct Journal {
+    path: PathBuf,
+}
+
+pub fn journal_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.as_os_str().to_owned();
+    name.push(".journal");
+    PathBuf::from(name)
+}
+
+impl Journal {
+    pub fn new(db_path: &Path) -> Self {
+        Self {
+            path: journal_path(db_path),
+        }
+    }
+
+    pub fn set_db_path(&mut self, db_path: &Path) {
+        self.path = journal_path(db_path);
+    }
+
+    /// takes the register as is, without writing anything
+    pub fn reset(&mut self, register: &mut ContentRegister<Content, Id>) {
+        register.take_touched();
+    }
+
+    /// writes what changed since the last record. only the items the register saw touched are compared.
+    /// returns the ids of the ones that did change
+    pub fn record(&mut self, register: &mut ContentRegister<Content, Id>) -> Result<Vec<Id>> {
+        let mut old = BTreeMap::new();
+        let mut new = BTreeMap::new();
+        let mut changed = vec![];
+        for (id, before, now) in register.take_touched() {
+            if before == now {
+                continue;
+            }
+            changed.push(id);
+            old.extend(before.map(|v| (id.raw(), v)));
+            new.extend(now.map(|v| (id.raw(), v)));
+        }
+        self.append(diff(&old, &new))?;
+        Ok(changed)
+    }
+
+    /// marks that the db now holds everything written so far. the journal is rewritten with only the
+    /// changes of the last days, as nothing before the snapshot is replayed
+    pub fn snapshot(&mut self) -> Result<()> {
+        let since = Local::now() - Duration::days(KEEP_DAYS);
+        let mut out = String::new();
+        for e in read_path(&self.path)? {
+            if e.at >= since && !matches!(e.change, Change::Snapshot) {
+                out.push_str(&serde_json::to_string(&e)?);
+                out.push('\n');
+            }
+        }
+        let tmp = self.path.with_extension("journal.tmp");
+        fs::write(&tmp, out).with_context(|| format!("failed to write journal {:?}", &tmp))?;
+        fs::rename(&tmp, &self.path)
+            .with_context(|| format!("failed to write journal {:?}", &self.path))?;
+        self.append(vec![(String::new(), Change::Snapshot)])
+    }
+
+    fn append(&self, changes: Vec<(String, Change)>) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let mut f = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("failed to open journal {:?}", &self.path))?;
+        let at = Local::now();
+        let mut out = String::new();
+        for (what, change) in changes {
+            out.push_str(&serde_json::to_string(&Entry { at, what, change })?);
+            out.push('\n');
+        }
+        f.write_all(out.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// all entries of the db's journal, oldest first
+pub fn read(db_path: &Path) -> Result<Vec<Entry>> {
+    read_path(&journal_path(db_path))
+}
+
+fn read_path(path: &Path) -> Result<Vec<Entry>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(_) => return Ok(vec![]),
+    };
+    // the last line might be cut off by a crash
+    Ok(text
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect())
+}
+
+/// the entries of the last few days without the snapshots, newest first
+pub fn recent(db_path: &Path, days: i64) -> Result<Vec<Entry>> {
+    let since = Local::now() - Duration::days(days);
+    let mut entries = read(db_path)?
+        .into_iter()
+        .filter(|e| e.at >= since && !matches!(e.change, Change::Snapshot))
+        .collect::<Vec<_>>();
+    entries.reverse();
+    Ok(entries)
+}
+
+/// applies the changes after the last snapshot, ie the ones that were not saved. returns how many there were
+pub fn replay(
+    db_path: &Path,
+    register: ContentRegister<Content, Id>,
+) -> Result<(ContentRegister<Content, Id>, usize)> {
+    let journal = read(db_path)?;
+    let start = journal
+        .iter()
+        .rposition(|e| matches!(e.change, Change::Snapshot))
+        .map_or(0, |i| i + 1);
+    let pending = &journal[start..];
+    if pending.is_empty() {
+        return Ok((register, 0));
+    }
+    let value = serde_yaml::to_value(&register)?;
+    let mut generation = generation(&value)?;
+    let mut items = entries(&value)?;
+    for e in pending {
+        apply(&mut items, &mut generation, &e.change);
+    }
+    let mut value = Mapping::new();
+    value.insert(
+        "items".into(),
+        Value::Sequence(items.into_values().collect()),
+    );
+    value.insert("generation".into(), generation.into());
+    let register =
+        serde_yaml::from_value(Value::Mapping(value)).context("failed to replay the journal")?;
+    Ok((register, pending.len()))
+}
+
+/// changes are applied by value where possible, so replaying something that was already saved does no harm
+fn apply(items: &mut BTreeMap<Key, Value>, generation: &mut u64, change: &Change) {
+    let entry = |id: &Value| key(id);
+    match change {
+        Change::Snapshot => (),
+        Change::Alloc { id, val } => {
+            if let Some(k) = entry(id) {
+                let mut e = Mapping::new();
+                e.insert("id".into(), id_value(k));
+                e.insert("val".into(), val.clone());
+                e.insert("generation".into(), k.1.into());
+                e.insert("id_counter".into(), 1.into());
+                items.insert(k, Value::Mapping(e));
+                *generation = (*generation).max(k.0 + 1);
+            }
+        }
+        Change::Unregister { id } => {
+            if let Some(k) = entry(id) {
+                items.remove(&k);
+            }
+        }
+        Change::Set { id, field, value } => {
+            if let Some(val) = entry(id)
+                .and_then(|k| items.get_mut(&k))
+                .and_then(|e| e.get_mut("val"))
+                .and_then(Value::as_mapping_mut)
+            {
+                val.insert(field.as_str().into(), value.clone());
+            }
+        }
+        Change::Insert { id, pos, item } => {
+            if let Some(list) = container_items(items, id) {
+                if !list.contains(item) {
+                    list.insert((*pos).min(list.len()), item.clone());
+                }
+            }
+        }
+        Change::Remove { id, item } => {
+            if let Some(list) = container_items(items, id) {
+                list.retain(|i| i != item);
+            }
+        }
+    }
+}
+
+fn container_items<'a>(
+    items: &'a mut BTreeMap<Key, Value>,
+    id: &Value,
+) -> Option<&'a mut Vec<Value>> {
+    match items
+        .get_mut(&key(id)?)?
+        .get_mut("val")?
+        .get_mut("container")?
+        .get_mut("items")?
+    {
+        Value::Sequence(s) => Some(s),
+        _ => None,
+    }
+}
+
+fn diff(old: &BTreeMap<Key, Value>, new: &BTreeMap<Key, Value>) -> Vec<(String, Change)> {
+    // new items first and deleted ones last, so containers never refer to missing items
+    let mut changes = new
+        .iter()
+        .filter(|(k, _)| !old.contains_key(k))
+        .map(|(k, n)| {
+            let val = n.get("val").cloned().unwrap_or_default();
+            (
+                label(Some(&val)),
+                Change::Alloc {
+                    id: id_value(*k),
+                    val,
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    for (k, n) in new {
+        let id = id_value(*k);
+        let nv = n.get("val").cloned().unwrap_or_default();
+        let what = label(Some(&nv));
+        let ov = match old.get(k) {
+            Some(o) => o.get("val").cloned().unwrap_or_default(),
+            None => continue,
+        };
+        if ov == nv {
+            continue;
+        }
+        let (om, nm) = match (ov.as_mapping(), nv.as_mapping()) {
+            (Some(om), Some(nm)) if ov.get("type") == nv.get("type") => (om, nm),
+            _ => {
+                changes.push((what, Change::Alloc { id, val: nv }));
+                continue;
+            }
+        };
+        for (field, nf) in nm {
+            let of = om.get(field);
+            if of == Some(nf) {
+                continue;
+            }
+            let field_name = field.as_str().unwrap_or_default().to_owned();
+            if field_name == "container" {
+                if let Some(list) = diff_items(&id, of, nf) {
+                    changes.extend(list.into_iter().map(|c| (what.clone(), c)));
+                    continue;
+                }
+            }
+            changes.push((
+                what.clone(),
+                Change::Set {
+                    id: id.clone(),
+                    field: field_name,
+                    value: nf.clone(),
+                },
+            ));
+        }
+        for (field, _) in om {
+            if !nm.contains_key(field) {
+                changes.push((
+                    what.clone(),
+                    Change::Set {
+                        id: id.clone(),
+                        field: field.as_str().unwrap_or_default().to_owned(),
+                        value: Value::Null,
+                    },
+                ));
+            }
+        }
+    }
+    for (k, o) in old {
+        if !new.contains_key(k) {
+            changes.push((label(o.get("val")), Change::Unregister { id: id_value(*k) }));
+        }
+    }
+    changes
+}
+
+/// inserts and removes turning the old container items into the new ones. None if the items were reordered
+fn diff_items(id: &Value, old: Option<&Value>, new: &Value) -> Option<Vec<Change>> {
+    let old = old?.get("items")?.as_sequence()?;
+    let new = new.get("items")?.as_sequence()?;
+    let mut changes = vec![];
+    let mut list = old.clone();
+    for item in old.iter().filter(|i| !new.contains(i)) {
+        list.retain(|i| i != item);
+        changes.push(Change::Remove {
+            id: id.clone(),
+            item: item.clone(),
+        });
+    }
+    for (pos, item) in new.iter().enumerate().filter(|(_, i)| !old.contains(i)) {
+        list.insert(pos.min(list.len()), item.clone());
+        changes.push(Change::Insert {
+            id: id.clone(),
+            pos,
+            item: item.clone(),
+        });
+    }
+    (&list == new).then_some(changes)
+}
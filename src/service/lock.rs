@@ -0,0 +1,119 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// the process holding a db
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Holder {
+    pub pid: u32,
+    pub host: String,
+}
+impl fmt::Display for Holder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pid {} on {}", self.pid, self.host)
+    }
+}
+
+impl Holder {
+    fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            host: hostname::get()
+                .map(|h| h.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        let mut lines = s.lines();
+        Some(Self {
+            pid: lines.next()?.trim().parse().ok()?,
+            host: lines.next()?.trim().to_owned(),
+        })
+    }
+
+    /// only processes on this host can be checked, locks from other hosts are assumed to be live
+    fn is_alive(&self) -> bool {
+        let proc = Path::new("/proc");
+        self.host != Holder::current().host
+            || !proc.exists()
+            || proc.join(self.pid.to_string()).exists()
+    }
+}
+
+/// db path -> who holds its lock. None if this process does
+static LOCKS: Lazy<Mutex<HashMap<PathBuf, Option<Holder>>>> = Lazy::new(Default::default);
+
+fn lock_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.as_os_str().to_owned();
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+/// takes the advisory lock next to the db. if another live process holds it, returns that process,
+/// the db should then only be read
+pub fn acquire(db_path: &Path) -> Result<Option<Holder>> {
+    let mut locks = LOCKS.lock().unwrap();
+    if let Some(holder) = locks.get(db_path) {
+        return Ok(holder.clone());
+    }
+    let path = lock_path(db_path);
+    let me = Holder::current();
+    let holder = loop {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut f) => {
+                writeln!(f, "{}\n{}", me.pid, me.host)?;
+                break None;
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                let other = fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|s| Holder::parse(&s));
+                match other {
+                    Some(other) if other == me => break None,
+                    Some(other) if other.is_alive() => break Some(other),
+                    _ => {
+                        debug!("removing stale lock {:?}", &path);
+                        fs::remove_file(&path).context("failed to remove stale lock")?;
+                    }
+                }
+            }
+            Err(e) => return Err(e).context(format!("failed to create lock {:?}", &path)),
+        }
+    };
+    locks.insert(db_path.to_owned(), holder.clone());
+    Ok(holder)
+}
+
+/// the other process holding the db, if we could not take the lock
+pub fn holder(db_path: &Path) -> Option<Holder> {
+    LOCKS.lock().unwrap().get(db_path).cloned().flatten()
+}
+
+pub fn release(db_path: &Path) {
+    if let Some(None) = LOCKS.lock().unwrap().remove(db_path) {
+        let _ = fs::remove_file(lock_path(db_path));
+    }
+}
+
+/// called on exit and from the panic hook
+pub fn release_all() {
+    // try_lock, the panic might have happened while holding it
+    if let Ok(mut locks) = LOCKS.try_lock() {
+        for (path, holder) in locks.drain() {
+            if holder.is_none() {
+                let _ = fs::remove_file(lock_path(&path));
+            }
+        }
+    }
+}
@@ -0,0 +1,296 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use chrono::{NaiveDate, NaiveTime, Timelike};
+use std::borrow::Cow;
+
+use crate::{
+    content::{
+        main_provider::MainProvider,
+        todo::{Date, Time, Todo, TodoStatus},
+        todo_list::TodoList,
+        traits::Content,
+    },
+    register::{ContentRegister, Id},
+    service::editors::Yank,
+};
+
+/// markdown checklists
+///
+/// # List
+/// - [ ] pending due:2022-10-18
+///   - [x] done subtask
+/// - [~] ignored
+pub fn export_main(register: &ContentRegister<Content, Id>, id: Id) -> String {
+    let content = register.get(id).unwrap();
+    let mp = content.as_any().downcast_ref::<MainProvider>().unwrap();
+    let mut out = format!("# {}\n", content.as_display().text());
+    // only the lists, not things like the statistics
+    for &list in mp.items().iter().filter(|&&id| {
+        register
+            .get(id)
+            .is_some_and(|c| c.as_any().is::<TodoList>())
+    }) {
+        out.push('\n');
+        export_list_at(register, list, 2, &mut out);
+    }
+    out
+}
+
+pub fn export_list(register: &ContentRegister<Content, Id>, id: Id) -> String {
+    let mut out = String::new();
+    export_list_at(register, id, 1, &mut out);
+    out
+}
+
+fn export_list_at(register: &ContentRegister<Content, Id>, id: Id, level: usize, out: &mut String) {
+    let content = register.get(id).unwrap();
+    out.push_str(&format!(
+        "{} {}\n",
+        "#".repeat(level),
+        content.as_display().text()
+    ));
+    if let Some(list) = content.as_any().downcast_ref::<TodoList>() {
+        export_items(register, list.items(), 0, out);
+    }
+}
+
+fn export_items(
+    register: &ContentRegister<Content, Id>,
+    items: &[Id],
+    depth: usize,
+    out: &mut String,
+) {
+    for &id in items {
+        let content = register.get(id).unwrap();
+        let indent = "  ".repeat(depth);
+        match content.as_any().downcast_ref::<Todo>() {
+            Some(t) => {
+                let check = match t.status {
+                    TodoStatus::Pending => ' ',
+                    TodoStatus::Done => 'x',
+                    TodoStatus::Ignored => '~',
+                };
+                out.push_str(&format!("{indent}- [{check}] {}", t.content));
+                if let Some(due) = format_due(t) {
+                    out.push_str(&format!(" due:{due}"));
+                }
+                out.push('\n');
+                if let Some(list) = t
+                    .subtasks
+                    .and_then(|id| register.get(id))
+                    .and_then(|c| c.as_any().downcast_ref::<TodoList>())
+                {
+                    export_items(register, list.items(), depth + 1, out);
+                }
+            }
+            None => {
+                out.push_str(&format!("{indent}- {}\n", content.as_display().text()));
+            }
+        }
+    }
+}
+
+fn format_due(t: &Todo) -> Option<String> {
+    let date = NaiveDate::from(t.due_date?).format("%Y-%m-%d");
+    Some(match &t.due_time {
+        Some(time) => format!("{date}T{:02}:{:02}", time.hour, time.min),
+        None => date.to_string(),
+    })
+}
+
+fn parse_due(s: &str) -> Option<(Date, Option<Time>)> {
+    let (date, time) = match s.split_once('T') {
+        Some((d, t)) => (d, Some(t)),
+        None => (s, None),
+    };
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%d-%m-%Y"))
+        .ok()?;
+    let time = time
+        .and_then(|t| NaiveTime::parse_from_str(t, "%H:%M").ok())
+        .map(|t| Time {
+            hour: t.hour() as _,
+            min: t.minute() as _,
+            sec: 0,
+        });
+    Some((date.into(), time))
+}
+
+/// parses a markdown checklist into new TodoLists. every heading starts a new list, nested bullets become subtasks.
+/// items before the first heading go into a list called default_title. returns the ids of the new lists
+pub fn import(
+    register: &mut ContentRegister<Content, Id>,
+    text: &str,
+    default_title: &str,
+) -> Vec<Id> {
+    let mut lists = vec![];
+    let mut title = default_title.to_owned();
+    let mut current: Option<Id> = None;
+    // (indent, todo id) of the bullets the next bullet might be nested under
+    let mut parents: Vec<(usize, Id)> = vec![];
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let indent = line[..line.len() - trimmed.len()]
+            .chars()
+            .map(|c| if c == '\t' { 4 } else { 1 })
+            .sum::<usize>();
+
+        // lists are only created once they get an item, so headings like the title of an exported MainProvider are skipped
+        if trimmed.starts_with('#') {
+            title = trimmed.trim_start_matches('#').trim().to_owned();
+            current = None;
+            parents.clear();
+            continue;
+        }
+
+        let item = match ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|b| trimmed.strip_prefix(b))
+        {
+            Some(item) => item,
+            None => continue,
+        };
+        let (status, item) = parse_checkbox(item);
+        let mut todo = Todo::new("");
+        todo.status = status;
+        let words = item
+            .split_whitespace()
+            .filter(|w| match w.strip_prefix("due:").and_then(parse_due) {
+                Some((date, time)) => {
+                    todo.due_date = Some(date);
+                    todo.due_time = time;
+                    false
+                }
+                None => true,
+            })
+            .collect::<Vec<_>>();
+        todo.content = Cow::Owned(words.join(" "));
+        let id = register.alloc(todo);
+
+        while parents.last().map(|&(i, _)| i >= indent).unwrap_or(false) {
+            parents.pop();
+        }
+        let dest = match parents.last() {
+            Some(&(_, parent)) => subtask_list(register, parent),
+            None => *current.get_or_insert_with(|| {
+                let id = register.alloc(TodoList::new(title.clone()));
+                lists.push(id);
+                id
+            }),
+        };
+        push(register, dest, id);
+        parents.push((indent, id));
+    }
+    lists
+}
+
+/// the box is followed by a space or ends the line, `[x]text` is just text
+fn parse_checkbox(item: &str) -> (TodoStatus, &str) {
+    let status = match item.get(..3) {
+        Some("[ ]") => TodoStatus::Pending,
+        Some("[x]") | Some("[X]") => TodoStatus::Done,
+        Some("[~]") => TodoStatus::Ignored,
+        _ => return (TodoStatus::Pending, item),
+    };
+    match &item[3..] {
+        rest if rest.is_empty() || rest.starts_with(char::is_whitespace) => (status, rest),
+        _ => (TodoStatus::Pending, item),
+    }
+}
+
+fn subtask_list(register: &mut ContentRegister<Content, Id>, parent: Id) -> Id {
+    let todo = register
+        .get(parent)
+        .unwrap()
+        .as_any()
+        .downcast_ref::<Todo>()
+        .unwrap();
+    match todo.subtasks {
+        Some(id) => id,
+        None => {
+            let title = todo.content.clone();
+            let id = register.alloc(TodoList::new(title));
+            register
+                .get_mut(parent)
+                .unwrap()
+                .as_any_mut()
+                .downcast_mut::<Todo>()
+                .unwrap()
+                .subtasks = Some(id);
+            id
+        }
+    }
+}
+
+fn push(register: &mut ContentRegister<Content, Id>, list: Id, id: Id) {
+    let list = register.get_mut(list).unwrap();
+    let pos = list
+        .as_any()
+        .downcast_ref::<TodoList>()
+        .unwrap()
+        .items()
+        .len();
+    list.as_yankdest().unwrap().insert(Yank { id, pos });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn todos(register: &ContentRegister<Content, Id>, list: Id) -> Vec<(String, TodoStatus)> {
+        register
+            .get(list)
+            .and_then(|c| c.as_any().downcast_ref::<TodoList>())
+            .unwrap()
+            .items()
+            .iter()
+            .filter_map(|&id| register.get(id)?.as_any().downcast_ref::<Todo>())
+            .map(|t| (t.content.to_string(), t.status))
+            .collect()
+    }
+
+    #[test]
+    fn exports_what_it_imported() {
+        let text = "\
+# Project
+- [ ] write docs due:2026-10-20
+  - [x] outline
+  - [ ] draft
+    - [~] pictures
+- [x] ship it due:2026-10-18T09:30
+";
+        let mut register = ContentRegister::new();
+        let lists = import(&mut register, text, "default");
+        assert_eq!(lists.len(), 1);
+        assert_eq!(export_list(&register, lists[0]), text);
+    }
+
+    #[test]
+    fn items_before_a_heading_go_into_the_default_list() {
+        let mut register = ContentRegister::new();
+        let lists = import(&mut register, "- a\n# Empty\n# Other\n* b\n", "inbox");
+        assert_eq!(lists.len(), 2);
+        assert_eq!(register.get(lists[0]).unwrap().as_display().text(), "inbox");
+        assert_eq!(register.get(lists[1]).unwrap().as_display().text(), "Other");
+        assert_eq!(
+            todos(&register, lists[1]),
+            [("b".to_owned(), TodoStatus::Pending)]
+        );
+    }
+
+    #[test]
+    fn a_box_needs_a_space_or_the_end_of_the_line_after_it() {
+        let mut register = ContentRegister::new();
+        let lists = import(&mut register, "- [x]\n- [X]\tdone\n- [x]nope\n", "l");
+        assert_eq!(
+            todos(&register, lists[0]),
+            [
+                ("".to_owned(), TodoStatus::Done),
+                ("done".to_owned(), TodoStatus::Done),
+                ("[x]nope".to_owned(), TodoStatus::Pending),
+            ]
+        );
+    }
+}
@@ -0,0 +1,557 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use anyhow::{Context, Result};
+use serde_yaml::{Mapping, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::Path,
+};
+
+use crate::{
+    content::traits::Content,
+    register::{ContentRegister, Id},
+    service::{
+        db::{DBFormat, DBHandler},
+        migrations, storage,
+    },
+};
+
+/// (id, generation) of a register entry
+pub type Key = (u64, u64);
+
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub id: Key,
+    /// text of the item, to find it again
+    pub what: String,
+    /// None if the whole item conflicts
+    pub field: Option<String>,
+    pub reason: &'static str,
+}
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} \"{}\"", self.id.0, self.what)?;
+        if let Some(field) = &self.field {
+            write!(f, " {field}")?;
+        }
+        write!(f, ": {}", self.reason)
+    }
+}
+
+/// three way merge of serialized registers, per item. items changed on only one side are taken from that side.
+/// items changed on both sides are merged field by field, and the items of containers by their order.
+/// when both sides changed the same field ours is kept and a conflict is returned.
+/// items added on both sides under the same id are both kept, theirs gets a new id
+pub fn merge_registers(
+    base: &Value,
+    ours: &Value,
+    theirs: &Value,
+) -> Result<(Value, Vec<Conflict>)> {
+    let base = entries(base)?;
+    let ours_map = entries(ours)?;
+    let theirs_map = entries(theirs)?;
+    let mut theirs = theirs.clone();
+    let mut generation = generation(ours)?.max(generation(&theirs)?);
+
+    // both sides allocate ids from the same counter, so new items on both sides can share an id
+    let clashes = ours_map
+        .iter()
+        .filter(|(k, o)| !base.contains_key(k) && theirs_map.get(k).is_some_and(|t| t != *o))
+        .map(|(k, _)| *k)
+        .collect::<Vec<_>>();
+    for old in clashes {
+        let new = (generation, generation);
+        generation += 1;
+        rekey(&mut theirs, old, new);
+    }
+    let theirs = entries(&theirs)?;
+
+    let keys = base
+        .keys()
+        .chain(ours_map.keys())
+        .chain(theirs.keys())
+        .cloned()
+        .collect::<BTreeSet<_>>();
+    let mut conflicts = vec![];
+    let mut merged = BTreeMap::new();
+    for key in keys {
+        let (b, o, t) = (base.get(&key), ours_map.get(&key), theirs.get(&key));
+        let entry = match (b, o, t) {
+            (_, Some(o), Some(t)) if o == t => Some(o.clone()),
+            (Some(b), Some(o), Some(t)) if o == b => Some(t.clone()),
+            (Some(b), Some(o), Some(t)) if t == b => Some(o.clone()),
+            (Some(b), Some(o), Some(t)) => Some(merge_entry(key, b, o, t, &mut conflicts)),
+            (Some(b), Some(o), None) | (Some(b), None, Some(o)) if o == b => None,
+            (Some(_), Some(o), None) | (Some(_), None, Some(o)) => {
+                conflicts.push(conflict(
+                    key,
+                    o,
+                    None,
+                    "changed on one side, deleted on the other",
+                ));
+                Some(o.clone())
+            }
+            (Some(_), None, None) => None,
+            (None, Some(o), None) | (None, None, Some(o)) => Some(o.clone()),
+            (None, Some(o), Some(_)) => Some(o.clone()), // clashes were rekeyed above
+            (None, None, None) => unreachable!(),
+        };
+        if let Some(e) = entry {
+            merged.insert(key, e);
+        }
+    }
+
+    // containers might still list items that were deleted on the other side
+    let alive = merged.keys().cloned().collect::<BTreeSet<_>>();
+    for e in merged.values_mut() {
+        if let Some(Value::Sequence(items)) = e
+            .get_mut("val")
+            .and_then(|v| v.get_mut("container"))
+            .and_then(|c| c.get_mut("items"))
+        {
+            items.retain(|id| key(id).is_some_and(|k| alive.contains(&k)));
+        }
+    }
+
+    let mut register = Mapping::new();
+    register.insert(
+        "items".into(),
+        Value::Sequence(merged.into_values().collect()),
+    );
+    register.insert("generation".into(), generation.into());
+    Ok((Value::Mapping(register), conflicts))
+}
+
+/// merges three db files into out. missing or empty files count as an empty db, like git passes for a base
+/// when both sides added the file. the output is in the format of ours (or out's extension if it has one)
+pub fn merge_files(base: &Path, ours: &Path, theirs: &Path, out: &Path) -> Result<Vec<Conflict>> {
+    let (b, _) = read_register(base)?;
+    let (o, format) = read_register(ours)?;
+    let (t, _) = read_register(theirs)?;
+    let (merged, conflicts) = merge_registers(&b, &o, &t)?;
+    let register: ContentRegister<Content, Id> =
+        serde_yaml::from_value(merged).context("merged db is invalid")?;
+    match out.extension().and_then(|e| e.to_str()) {
+        Some("sqlite") | Some("sqlite3") | Some("db") => storage::open(out)?.save(&register)?,
+        Some("json") | Some("yaml") | Some("yml") => std::fs::write(
+            out,
+            DBFormat::from_path(out).to_string(&DBHandler::from_register(register))?,
+        )?,
+        _ => std::fs::write(out, format.to_string(&DBHandler::from_register(register))?)?,
+    }
+    Ok(conflicts)
+}
+
+/// the serialized register of a db file and the format it was in. does not write backups when migrating,
+/// the files are usually temporary
+fn read_register(path: &Path) -> Result<(Value, DBFormat)> {
+    let empty = || serde_yaml::to_value(ContentRegister::<Content, Id>::new());
+    if let Some("sqlite") | Some("sqlite3") | Some("db") = path.extension().and_then(|e| e.to_str())
+    {
+        let v = match storage::open(path)?.load()? {
+            Some(db) => serde_yaml::to_value(&db.register)?,
+            None => empty()?,
+        };
+        return Ok((v, DBFormat::Yaml));
+    }
+    let text = std::fs::read_to_string(path).unwrap_or_default();
+    let format = match path.extension() {
+        Some(_) => DBFormat::from_path(path),
+        None if text.trim_start().starts_with('{') => DBFormat::Json,
+        None => DBFormat::Yaml,
+    };
+    if text.trim().is_empty() {
+        return Ok((empty()?, format));
+    }
+    let (db, _) = migrations::migrate(format.parse(&text)?)?;
+    let register = db
+        .get("register")
+        .cloned()
+        .with_context(|| format!("{path:?} has no register"))?;
+    Ok((register, format))
+}
+
+/// an item changed on both sides
+fn merge_entry(
+    id: Key,
+    base: &Value,
+    ours: &Value,
+    theirs: &Value,
+    conflicts: &mut Vec<Conflict>,
+) -> Value {
+    let mut entry = ours.clone();
+    let (b, o, t) = match (base.get("val"), ours.get("val"), theirs.get("val")) {
+        (Some(b), Some(o), Some(t)) => (b, o, t),
+        _ => {
+            conflicts.push(conflict(id, ours, None, "changed on both sides"));
+            return entry;
+        }
+    };
+    let same_type = [b, t].iter().all(|v| v.get("type") == o.get("type"));
+    let (bm, om, tm) = match (b.as_mapping(), o.as_mapping(), t.as_mapping()) {
+        (Some(bm), Some(om), Some(tm)) if same_type => (bm, om, tm),
+        _ => {
+            conflicts.push(conflict(id, ours, None, "changed on both sides"));
+            return entry;
+        }
+    };
+
+    let fields = om
+        .iter()
+        .chain(tm.iter())
+        .map(|(k, _)| k.clone())
+        .collect::<Vec<_>>();
+    let mut val = Mapping::new();
+    for field in fields {
+        if val.contains_key(&field) {
+            continue;
+        }
+        let (fb, fo, ft) = (bm.get(&field), om.get(&field), tm.get(&field));
+        let merged = match (fb, fo, ft) {
+            (_, Some(fo), Some(ft)) if fo == ft => fo.clone(),
+            (fb, Some(fo), Some(ft)) if fb == Some(fo) => ft.clone(),
+            (fb, Some(fo), Some(ft)) if fb == Some(ft) => fo.clone(),
+            (fb, Some(fo), Some(ft)) if field.as_str() == Some("container") => {
+                match merge_container(fb, fo, ft) {
+                    Some(c) => c,
+                    None => {
+                        conflicts.push(conflict(id, ours, Some(&field), "reordered on both sides"));
+                        fo.clone()
+                    }
+                }
+            }
+            (_, Some(fo), Some(_)) => {
+                conflicts.push(conflict(id, ours, Some(&field), "changed on both sides"));
+                fo.clone()
+            }
+            (_, Some(f), None) | (_, None, Some(f)) => f.clone(),
+            (_, None, None) => unreachable!(),
+        };
+        val.insert(field, merged);
+    }
+    entry["val"] = Value::Mapping(val);
+    entry
+}
+
+/// merges the items of a Container by their order. None if both sides reordered the same items differently
+fn merge_container(base: Option<&Value>, ours: &Value, theirs: &Value) -> Option<Value> {
+    let items = |c: Option<&Value>| {
+        c.and_then(|c| c.get("items"))
+            .and_then(Value::as_sequence)
+            .cloned()
+            .unwrap_or_default()
+    };
+    let (items, ordered) = merge_order(&items(base), &items(Some(ours)), &items(Some(theirs)));
+    let mut c = ours.clone();
+    c["items"] = Value::Sequence(items);
+    ordered.then_some(c)
+}
+
+/// items removed on either side are removed, items added on either side are inserted after the item they followed.
+/// if only one side moved items around, its order is kept. returns false if both did
+fn merge_order(base: &[Value], ours: &[Value], theirs: &[Value]) -> (Vec<Value>, bool) {
+    let kept = |v: &&Value| !base.contains(v) || (ours.contains(v) && theirs.contains(v));
+    let ours = ours.iter().filter(kept).cloned().collect::<Vec<_>>();
+    let theirs = theirs.iter().filter(kept).cloned().collect::<Vec<_>>();
+    let in_base = |items: &[Value]| {
+        items
+            .iter()
+            .filter(|v| base.contains(v))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    let base_order = base
+        .iter()
+        .filter(|v| ours.contains(v))
+        .cloned()
+        .collect::<Vec<_>>();
+    let (ours_order, theirs_order) = (in_base(&ours), in_base(&theirs));
+
+    let (mut merged, other, ordered) = if ours_order == base_order {
+        (theirs, ours, true)
+    } else {
+        let ordered = theirs_order == base_order || theirs_order == ours_order;
+        (ours, theirs, ordered)
+    };
+    for (i, v) in other.iter().enumerate() {
+        if merged.contains(v) {
+            continue;
+        }
+        let pos = other[..i]
+            .iter()
+            .rev()
+            .find_map(|prev| merged.iter().position(|m| m == prev))
+            .map_or(0, |p| p + 1);
+        merged.insert(pos, v.clone());
+    }
+    (merged, ordered)
+}
+
+fn conflict(id: Key, entry: &Value, field: Option<&Value>, reason: &'static str) -> Conflict {
+    Conflict {
+        id,
+        what: label(entry.get("val")),
+        field: field.and_then(Value::as_str).map(str::to_owned),
+        reason,
+    }
+}
+
+/// the text of a serialized item
+pub fn label(val: Option<&Value>) -> String {
+    ["content", "title"]
+        .iter()
+        .find_map(|f| val.and_then(|v| v.get(f)).and_then(Value::as_str))
+        .unwrap_or_default()
+        .to_owned()
+}
+
+pub fn generation(register: &Value) -> Result<u64> {
+    register
+        .get("generation")
+        .and_then(Value::as_u64)
+        .context("register has no generation")
+}
+
+pub fn key(id: &Value) -> Option<Key> {
+    Some((id.get("id")?.as_u64()?, id.get("generation")?.as_u64()?))
+}
+
+/// the register's entries by their id
+pub fn entries(register: &Value) -> Result<BTreeMap<Key, Value>> {
+    register
+        .get("items")
+        .and_then(Value::as_sequence)
+        .context("register has no items")?
+        .iter()
+        .map(|e| {
+            let k = e.get("id").and_then(key).context("entry has no id")?;
+            Ok((k, e.clone()))
+        })
+        .collect()
+}
+
+pub fn id_value(key: Key) -> Value {
+    let mut m = Mapping::new();
+    m.insert("id".into(), key.0.into());
+    m.insert("generation".into(), key.1.into());
+    Value::Mapping(m)
+}
+
+/// replaces every occurrence of the id (the entry's own id and all references to it)
+fn rekey(v: &mut Value, old: Key, new: Key) {
+    if v.as_mapping().is_some_and(|m| m.len() == 2) && key(v) == Some(old) {
+        *v = id_value(new);
+        return;
+    }
+    match v {
+        Value::Mapping(m) => {
+            // entries store the generation of their id next to it
+            if m.get(&"id".into()).and_then(key) == Some(old) {
+                m.insert("generation".into(), new.1.into());
+            }
+            m.iter_mut().for_each(|(_, v)| rekey(v, old, new));
+        }
+        Value::Sequence(s) => s.iter_mut().for_each(|v| rekey(v, old, new)),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn todo(id: u64, content: &str, status: &str) -> Value {
+        serde_yaml::from_str(&format!(
+            "id: {{id: {id}, generation: {id}}}
+val: {{type: Todo, content: {content}, status: {status}}}
+generation: {id}
+id_counter: 1"
+        ))
+        .unwrap()
+    }
+
+    fn list(id: u64, items: &[u64]) -> Value {
+        let mut e: Value = serde_yaml::from_str(&format!(
+            "id: {{id: {id}, generation: {id}}}
+val: {{type: TodoList, title: list, container: {{items: []}}}}
+generation: {id}
+id_counter: 1"
+        ))
+        .unwrap();
+        e["val"]["container"]["items"] = ids(items).into();
+        e
+    }
+
+    fn register(entries: Vec<Value>) -> Value {
+        let mut m = Mapping::new();
+        m.insert("items".into(), Value::Sequence(entries));
+        m.insert("generation".into(), 10.into());
+        Value::Mapping(m)
+    }
+
+    fn ids(items: &[u64]) -> Vec<Value> {
+        items.iter().map(|&i| id_value((i, i))).collect()
+    }
+
+    fn val(register: &Value, id: Key) -> Option<&Value> {
+        register
+            .get("items")?
+            .as_sequence()?
+            .iter()
+            .find(|e| e.get("id").and_then(key) == Some(id))?
+            .get("val")
+    }
+
+    #[test]
+    fn takes_the_side_that_changed() {
+        let base = register(vec![todo(1, "a", "Pending"), todo(2, "b", "Pending")]);
+        let ours = register(vec![todo(1, "a", "Done"), todo(2, "b", "Pending")]);
+        let theirs = register(vec![todo(1, "a", "Pending"), todo(2, "c", "Pending")]);
+        let (merged, conflicts) = merge_registers(&base, &ours, &theirs).unwrap();
+        assert!(conflicts.is_empty());
+        assert_eq!(val(&merged, (1, 1)).unwrap()["status"], "Done");
+        assert_eq!(val(&merged, (2, 2)).unwrap()["content"], "c");
+    }
+
+    #[test]
+    fn merges_different_fields_of_an_item() {
+        let base = register(vec![todo(1, "a", "Pending")]);
+        let ours = register(vec![todo(1, "b", "Pending")]);
+        let theirs = register(vec![todo(1, "a", "Done")]);
+        let (merged, conflicts) = merge_registers(&base, &ours, &theirs).unwrap();
+        assert!(conflicts.is_empty());
+        let v = val(&merged, (1, 1)).unwrap();
+        assert_eq!(v["content"], "b");
+        assert_eq!(v["status"], "Done");
+    }
+
+    #[test]
+    fn keeps_ours_when_both_changed_a_field() {
+        let base = register(vec![todo(1, "a", "Pending")]);
+        let ours = register(vec![todo(1, "b", "Pending")]);
+        let theirs = register(vec![todo(1, "c", "Pending")]);
+        let (merged, conflicts) = merge_registers(&base, &ours, &theirs).unwrap();
+        assert_eq!(val(&merged, (1, 1)).unwrap()["content"], "b");
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].id, (1, 1));
+        assert_eq!(conflicts[0].field.as_deref(), Some("content"));
+    }
+
+    #[test]
+    fn deletes_unchanged_items_and_their_references() {
+        let base = register(vec![
+            list(1, &[2, 3]),
+            todo(2, "a", "Pending"),
+            todo(3, "b", "Pending"),
+        ]);
+        let ours = register(vec![list(1, &[3]), todo(3, "b", "Pending")]);
+        let theirs = base.clone();
+        let (merged, conflicts) = merge_registers(&base, &ours, &theirs).unwrap();
+        assert!(conflicts.is_empty());
+        assert!(val(&merged, (2, 2)).is_none());
+        assert_eq!(
+            val(&merged, (1, 1)).unwrap()["container"]["items"],
+            Value::Sequence(ids(&[3]))
+        );
+    }
+
+    #[test]
+    fn keeps_an_item_deleted_on_one_side_and_changed_on_the_other() {
+        let base = register(vec![todo(1, "a", "Pending")]);
+        let ours = register(vec![]);
+        let theirs = register(vec![todo(1, "a", "Done")]);
+        let (merged, conflicts) = merge_registers(&base, &ours, &theirs).unwrap();
+        assert_eq!(val(&merged, (1, 1)).unwrap()["status"], "Done");
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            conflicts[0].reason,
+            "changed on one side, deleted on the other"
+        );
+    }
+
+    #[test]
+    fn gives_theirs_a_new_id_when_both_added_under_the_same() {
+        let base = register(vec![list(1, &[])]);
+        let ours = register(vec![list(1, &[2]), todo(2, "ours", "Pending")]);
+        let theirs = register(vec![list(1, &[2]), todo(2, "theirs", "Pending")]);
+        let (merged, conflicts) = merge_registers(&base, &ours, &theirs).unwrap();
+        assert!(conflicts.is_empty());
+        assert_eq!(val(&merged, (2, 2)).unwrap()["content"], "ours");
+        assert_eq!(val(&merged, (10, 10)).unwrap()["content"], "theirs");
+        assert_eq!(
+            val(&merged, (1, 1)).unwrap()["container"]["items"],
+            Value::Sequence(vec![id_value((2, 2)), id_value((10, 10))])
+        );
+        assert_eq!(merged["generation"], 11);
+    }
+
+    #[test]
+    fn conflicts_when_both_reordered_a_list() {
+        let todos = (2..=4).map(|i| todo(i, "a", "Pending"));
+        let with = |l| register([l].into_iter().chain(todos.clone()).collect());
+        let base = with(list(1, &[2, 3, 4]));
+        let ours = with(list(1, &[3, 2, 4]));
+        let theirs = with(list(1, &[2, 4, 3]));
+        let (merged, conflicts) = merge_registers(&base, &ours, &theirs).unwrap();
+        assert_eq!(
+            val(&merged, (1, 1)).unwrap()["container"]["items"],
+            Value::Sequence(ids(&[3, 2, 4]))
+        );
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].field.as_deref(), Some("container"));
+    }
+
+    fn order(base: &[u64], ours: &[u64], theirs: &[u64]) -> (Vec<u64>, bool) {
+        let v = |items: &[u64]| items.iter().map(|&i| Value::from(i)).collect::<Vec<_>>();
+        let (merged, ordered) = merge_order(&v(base), &v(ours), &v(theirs));
+        (
+            merged.iter().map(|v| v.as_u64().unwrap()).collect(),
+            ordered,
+        )
+    }
+
+    #[test]
+    fn inserts_added_items_after_the_one_they_followed() {
+        assert_eq!(
+            order(&[1, 2, 3], &[1, 2, 4, 3], &[1, 5, 2, 3]),
+            (vec![1, 5, 2, 4, 3], true)
+        );
+        assert_eq!(order(&[1], &[0, 1], &[1]), (vec![0, 1], true));
+    }
+
+    #[test]
+    fn removes_items_removed_on_either_side() {
+        assert_eq!(
+            order(&[1, 2, 3], &[1, 3], &[1, 2, 3, 4]),
+            (vec![1, 3, 4], true)
+        );
+        assert_eq!(order(&[1, 2, 3], &[1, 2, 3], &[2, 3]), (vec![2, 3], true));
+    }
+
+    #[test]
+    fn keeps_the_order_of_the_side_that_reordered() {
+        assert_eq!(
+            order(&[1, 2, 3], &[3, 1, 2], &[1, 2, 3, 4]),
+            (vec![3, 4, 1, 2], true)
+        );
+        assert_eq!(
+            order(&[1, 2, 3], &[1, 2, 3], &[2, 1, 3]),
+            (vec![2, 1, 3], true)
+        );
+        // the same new order on both sides is no conflict
+        assert_eq!(
+            order(&[1, 2, 3], &[3, 2, 1], &[3, 2, 1]),
+            (vec![3, 2, 1], true)
+        );
+    }
+
+    #[test]
+    fn keeps_ours_when_both_reordered() {
+        assert_eq!(
+            order(&[1, 2, 3], &[2, 1, 3], &[1, 3, 2]),
+            (vec![2, 1, 3], false)
+        );
+    }
+}
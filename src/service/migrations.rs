@@ -0,0 +1,65 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use anyhow::{bail, Context, Result};
+use serde_yaml::{Mapping, Value};
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+type Migration = fn(&mut Value) -> Result<()>;
+
+/// MIGRATIONS[n] takes a db from version n to n+1
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [v0_to_v1];
+
+/// brings the db up to SCHEMA_VERSION. returns the migrated db and the version it was at
+pub fn migrate(mut db: Value) -> Result<(Value, u32)> {
+    let from = db
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0) as u32;
+    if from > SCHEMA_VERSION {
+        bail!("db has schema version {from}, but this wildo only knows up to {SCHEMA_VERSION}");
+    }
+    for (v, migration) in MIGRATIONS.iter().enumerate().skip(from as _) {
+        migration(&mut db).with_context(|| format!("failed to migrate db from v{v}"))?;
+    }
+    db.as_mapping_mut()
+        .context("db is not a map")?
+        .insert("schema_version".into(), SCHEMA_VERSION.into());
+    Ok((db, from))
+}
+
+fn field<'a>(v: &'a mut Value, name: &str) -> Result<&'a mut Value> {
+    v.get_mut(name)
+        .with_context(|| format!("missing field {name}"))
+}
+
+/// register items went from a map with ContentID keys to a list of entries with the ContentID in them
+fn v0_to_v1(db: &mut Value) -> Result<()> {
+    let items = field(field(db, "register")?, "items")?;
+    let map = match items {
+        Value::Mapping(map) => std::mem::take(map),
+        _ => bail!("register items is not a map"),
+    };
+    let entries = map
+        .into_iter()
+        .map(|(id, entry)| {
+            let mut entry = match entry {
+                Value::Mapping(m) => m,
+                _ => bail!("register entry is not a map"),
+            };
+            let mut e = Mapping::new();
+            e.insert("id".into(), id);
+            e.insert(
+                "val".into(),
+                entry.remove(&"val".into()).context("missing val")?,
+            );
+            for (k, v) in entry {
+                e.insert(k, v);
+            }
+            Ok(Value::Mapping(e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    *items = Value::Sequence(entries);
+    Ok(())
+}
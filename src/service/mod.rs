@@ -1,5 +1,20 @@
 pub mod config;
+pub mod csv;
 pub mod db;
 pub mod editors;
+pub mod ical;
 pub mod insert_mode;
+pub mod journal;
+pub mod lock;
 pub mod log;
+pub mod markdown;
+pub mod merge;
+pub mod migrations;
+pub mod notify;
+pub mod pomodoro;
+pub mod remind;
+pub mod sqlite;
+pub mod storage;
+pub mod theme;
+pub mod tree;
+pub mod watch;
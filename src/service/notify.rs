@@ -0,0 +1,36 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use crate::service::config::config;
+
+/// runs the notify_command from the config, or rings the terminal bell without one
+pub fn notify(title: &str, body: &str) {
+    let cmd = match &config().notify_command {
+        Some(cmd) => cmd,
+        None => {
+            let mut out = std::io::stdout();
+            let _ = out.write_all(b"\x07").and_then(|_| out.flush());
+            return;
+        }
+    };
+    let args = cmd
+        .iter()
+        .map(|a| a.replace("{title}", title).replace("{body}", body));
+    let mut args = args.collect::<Vec<_>>().into_iter();
+    let program = args.next().unwrap_or_default();
+    // no waiting, the child is reaped whenever. a missing program only gets logged
+    if let Err(err) = Command::new(&program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        error!("failed to run notify command {program}: {err}");
+    }
+}
@@ -0,0 +1,130 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use chrono::{DateTime, Duration, Local};
+
+use crate::{
+    content::{
+        todo::{running_timer, Todo},
+        traits::Content,
+    },
+    register::{ContentRegister, Id},
+    service::config::config,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Work => "work",
+            Self::ShortBreak => "break",
+            Self::LongBreak => "long break",
+        }
+    }
+
+    fn length(&self) -> Duration {
+        let c = &config().pomodoro;
+        Duration::minutes(match self {
+            Self::Work => c.work,
+            Self::ShortBreak => c.short_break,
+            Self::LongBreak => c.long_break,
+        } as i64)
+    }
+}
+
+/// a running pomodoro. the time of the todo is tracked during work phases
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub todo: Id,
+    pub phase: Phase,
+    pub ends_at: DateTime<Local>,
+    /// work phases finished in this session
+    pub done: u32,
+}
+
+impl Session {
+    pub fn start(todo: Id, register: &mut ContentRegister<Content, Id>) -> Self {
+        let s = Self {
+            todo,
+            phase: Phase::Work,
+            ends_at: Local::now() + Phase::Work.length(),
+            done: 0,
+        };
+        s.track(register);
+        s
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.ends_at - Local::now()
+    }
+
+    /// stops the time tracking of the todo
+    pub fn stop(&self, register: &mut ContentRegister<Content, Id>) {
+        if let Some(t) = todo_mut(register, self.todo) {
+            t.stop_timer();
+        }
+    }
+
+    /// moves on to the next phase if this one is over. returns the message for the change.
+    /// None as the session if the todo is gone
+    pub fn tick(
+        mut self,
+        register: &mut ContentRegister<Content, Id>,
+    ) -> (Option<Self>, Option<String>) {
+        if todo_mut(register, self.todo).is_none() {
+            return (None, None);
+        }
+        if self.remaining() > Duration::zero() {
+            return (Some(self), None);
+        }
+        let every = config().pomodoro.long_break_every;
+        self.phase = match self.phase {
+            Phase::Work => {
+                self.done += 1;
+                if let Some(t) = todo_mut(register, self.todo) {
+                    t.pomodoros.push(Local::now());
+                }
+                match self.done % every {
+                    0 => Phase::LongBreak,
+                    _ => Phase::ShortBreak,
+                }
+            }
+            Phase::ShortBreak | Phase::LongBreak => Phase::Work,
+        };
+        self.ends_at = Local::now() + self.phase.length();
+        self.track(register);
+        let message = format!(
+            "{} for {} minutes",
+            self.phase.name(),
+            self.phase.length().num_minutes()
+        );
+        (Some(self), Some(message))
+    }
+
+    fn track(&self, register: &mut ContentRegister<Content, Id>) {
+        if self.phase != Phase::Work {
+            return self.stop(register);
+        }
+        // only one timer runs at a time
+        if let Some((id, _)) = running_timer(register).filter(|(id, _)| *id != self.todo) {
+            if let Some(t) = todo_mut(register, id) {
+                t.stop_timer();
+            }
+        }
+        if let Some(t) = todo_mut(register, self.todo) {
+            t.start_timer();
+        }
+    }
+}
+
+fn todo_mut(register: &mut ContentRegister<Content, Id>, id: Id) -> Option<&mut Todo> {
+    register
+        .get_mut(id)
+        .and_then(|c| c.as_any_mut().downcast_mut::<Todo>())
+}
@@ -0,0 +1,144 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDateTime};
+use std::collections::HashMap;
+
+use crate::{
+    content::{
+        todo::{format_duration, Todo, TodoStatus},
+        traits::Content,
+    },
+    register::{ContentRegister, Id},
+    service::{
+        config::{config, profile},
+        notify::notify,
+        storage, tree,
+        watch::FileStamp,
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct Reminder {
+    pub id: Id,
+    pub title: String,
+    pub body: String,
+}
+
+impl Reminder {
+    pub fn line(&self) -> String {
+        format!("{}: {}", self.title, self.body)
+    }
+}
+
+/// remembers which reminders went off, so each lead time of a todo fires once
+#[derive(Debug, Default)]
+pub struct Reminders {
+    /// by raw id, the due moment a todo had and how many of the lead times were reached for it
+    fired: HashMap<(u64, u64), (NaiveDateTime, usize)>,
+}
+
+impl Reminders {
+    /// the reminders that are due since the last check. when a check reaches several lead times
+    /// of a todo at once (eg on startup) only the last one fires
+    pub fn check(&mut self, register: &ContentRegister<Content, Id>) -> Vec<Reminder> {
+        let now = Local::now().naive_local();
+        let leads = &config().remind_before;
+        let mut todos = pending(register).collect::<Vec<_>>();
+        todos.sort_by_key(|(_, _, due)| *due);
+        let mut out = vec![];
+        for (id, t, due) in todos {
+            let reached = leads
+                .iter()
+                .filter(|&&m| due - Duration::minutes(m as i64) <= now)
+                .count();
+            if reached == 0 {
+                continue;
+            }
+            let seen = self
+                .fired
+                .get(&id.raw())
+                .filter(|(d, _)| *d == due)
+                .map_or(0, |(_, n)| *n);
+            if reached > seen {
+                self.fired.insert(id.raw(), (due, reached));
+                out.push(reminder(id, t, due, now));
+            }
+        }
+        out
+    }
+}
+
+/// pending todos with a due date, only the ones that can be reached from the main provider
+fn pending(
+    register: &ContentRegister<Content, Id>,
+) -> impl Iterator<Item = (Id, &Todo, NaiveDateTime)> {
+    let shown = tree::reachable_ids(register);
+    register.iter().filter_map(move |(id, c)| {
+        if !shown.contains(&id) {
+            return None;
+        }
+        let t = c
+            .as_any()
+            .downcast_ref::<Todo>()
+            .filter(|t| t.status == TodoStatus::Pending)?;
+        Some((id, t, t.due()?))
+    })
+}
+
+fn reminder(id: Id, t: &Todo, due: NaiveDateTime, now: NaiveDateTime) -> Reminder {
+    let body = if due > now {
+        format!("due in {}", format_duration(due - now))
+    } else if now - due < Duration::minutes(1) {
+        "due now".to_owned()
+    } else {
+        format!("overdue since {}", due.format("%d-%m-%Y %H:%M"))
+    };
+    Reminder {
+        id,
+        title: t.content.to_string(),
+        body,
+    }
+}
+
+/// overdue todos and the ones within the longest lead time, soonest first
+pub fn upcoming(register: &ContentRegister<Content, Id>) -> Vec<Reminder> {
+    let now = Local::now().naive_local();
+    let lead = Duration::minutes(config().remind_before.first().copied().unwrap_or(0) as i64);
+    let mut due = pending(register)
+        .filter(|(_, _, due)| *due - lead <= now)
+        .collect::<Vec<_>>();
+    due.sort_by_key(|(_, _, due)| *due);
+    due.into_iter()
+        .map(|(id, t, due)| reminder(id, t, due, now))
+        .collect()
+}
+
+/// reminds without the tui. reads the db whenever it changes and never writes it, so it can run next to the app
+pub async fn daemon() -> Result<()> {
+    let path = &profile().db_path;
+    let mut stamp = None;
+    let mut register = ContentRegister::new();
+    let mut reminders = Reminders::default();
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
+    loop {
+        interval.tick().await;
+        if let Some(s) = FileStamp::changed(stamp.as_ref(), path) {
+            match storage::open_read_only(path).and_then(|mut s| s.load()) {
+                Ok(Some(db)) => register = db.register,
+                Ok(None) => (),
+                // probably caught in the middle of a save, try again on the next tick
+                Err(err) => {
+                    eprintln!("failed to read the db: {err}");
+                    continue;
+                }
+            }
+            stamp = Some(s);
+        }
+        for r in reminders.check(&register) {
+            println!("{} {}", Local::now().format("%H:%M"), r.line());
+            notify(&r.title, &r.body);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use anyhow::Result;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, Transaction};
+use serde_yaml::{Mapping, Value};
+use std::{collections::HashMap, path::Path, time::Duration};
+
+use crate::{
+    content::traits::Content,
+    register::{ContentRegister, Id},
+    service::{db::DBHandler, migrations, storage::Storage},
+};
+
+/// every item in the register is a row, so saves only touch the items that changed
+pub struct SqliteStorage {
+    conn: Connection,
+}
+
+impl SqliteStorage {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS meta (
+                key TEXT PRIMARY KEY,
+                value INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS content (
+                id INTEGER NOT NULL,
+                generation INTEGER NOT NULL,
+                id_counter INTEGER NOT NULL,
+                val TEXT NOT NULL,
+                PRIMARY KEY (id, generation)
+            );
+            ",
+        )?;
+        Ok(Self { conn })
+    }
+
+    /// fails if there is no db yet, as nothing is created
+    pub fn open_read_only(path: &Path) -> Result<Self> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        Ok(Self { conn })
+    }
+
+    fn meta(&self, key: &str) -> Result<Option<u64>> {
+        let v = self
+            .conn
+            .query_row("SELECT value FROM meta WHERE key = ?1", [key], |r| {
+                r.get::<_, i64>(0)
+            })
+            .optional()?;
+        Ok(v.map(|v| v as _))
+    }
+
+    fn set_meta(tx: &Transaction, key: &str, value: u64) -> Result<()> {
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+            params![key, value as i64],
+        )?;
+        Ok(())
+    }
+
+    fn write_entry(
+        tx: &Transaction,
+        register: &ContentRegister<Content, Id>,
+        id: Id,
+    ) -> Result<()> {
+        let (val, id_counter) = match register.entry(id) {
+            Some(e) => e,
+            None => return Ok(()),
+        };
+        let (i, g) = id.raw();
+        tx.execute(
+            "INSERT OR REPLACE INTO content (id, generation, id_counter, val) VALUES (?1, ?2, ?3, ?4)",
+            params![i as i64, g as i64, id_counter, serde_json::to_string(val)?],
+        )?;
+        Ok(())
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load(&mut self) -> Result<Option<DBHandler>> {
+        let version = match self.meta("schema_version")? {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let generation = self.meta("generation")?.unwrap_or(0);
+
+        // rebuild the db as it would be in a file, so that it goes through the same migrations
+        let mut stmt = self.conn.prepare(
+            "SELECT id, generation, id_counter, val FROM content ORDER BY id, generation",
+        )?;
+        let items = stmt
+            .query_map([], |r| {
+                Ok((
+                    r.get::<_, i64>(0)?,
+                    r.get::<_, i64>(1)?,
+                    r.get::<_, u32>(2)?,
+                    r.get::<_, String>(3)?,
+                ))
+            })?
+            .map(|row| {
+                let (id, gen, id_counter, val) = row?;
+                let mut key = Mapping::new();
+                key.insert("id".into(), id.into());
+                key.insert("generation".into(), gen.into());
+                let mut entry = Mapping::new();
+                entry.insert("id".into(), Value::Mapping(key));
+                entry.insert("val".into(), serde_json::from_str::<Value>(&val)?);
+                entry.insert("generation".into(), gen.into());
+                entry.insert("id_counter".into(), id_counter.into());
+                Ok(Value::Mapping(entry))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut register = Mapping::new();
+        register.insert("items".into(), Value::Sequence(items));
+        register.insert("generation".into(), generation.into());
+        let mut db = Mapping::new();
+        db.insert("schema_version".into(), version.into());
+        db.insert("register".into(), Value::Mapping(register));
+
+        let (db, _) = migrations::migrate(Value::Mapping(db))?;
+        Ok(Some(serde_yaml::from_value(db)?))
+    }
+
+    fn save(&mut self, register: &ContentRegister<Content, Id>) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        let stored = {
+            let mut stmt = tx.prepare("SELECT id, generation, id_counter, val FROM content")?;
+            let rows = stmt.query_map([], |r| {
+                Ok((
+                    (r.get::<_, i64>(0)?, r.get::<_, i64>(1)?),
+                    (r.get::<_, u32>(2)?, r.get::<_, String>(3)?),
+                ))
+            })?;
+            rows.collect::<rusqlite::Result<HashMap<_, _>>>()?
+        };
+
+        let mut live = HashMap::new();
+        for (id, val) in register.iter() {
+            let (i, g) = id.raw();
+            let key = (i as i64, g as i64);
+            let (_, id_counter) = register.entry(id).unwrap();
+            let row = (id_counter, serde_json::to_string(val)?);
+            if stored.get(&key) != Some(&row) {
+                Self::write_entry(&tx, register, id)?;
+            }
+            live.insert(key, ());
+        }
+        for key in stored.keys().filter(|k| !live.contains_key(k)) {
+            tx.execute(
+                "DELETE FROM content WHERE id = ?1 AND generation = ?2",
+                params![key.0, key.1],
+            )?;
+        }
+        Self::set_meta(&tx, "schema_version", migrations::SCHEMA_VERSION as _)?;
+        Self::set_meta(&tx, "generation", register.generation())?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn upsert(&mut self, register: &ContentRegister<Content, Id>, id: Id) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        Self::write_entry(&tx, register, id)?;
+        Self::set_meta(&tx, "schema_version", migrations::SCHEMA_VERSION as _)?;
+        Self::set_meta(&tx, "generation", register.generation())?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete(&mut self, register: &ContentRegister<Content, Id>, id: Id) -> Result<()> {
+        let (i, g) = id.raw();
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "DELETE FROM content WHERE id = ?1 AND generation = ?2",
+            params![i as i64, g as i64],
+        )?;
+        Self::set_meta(&tx, "generation", register.generation())?;
+        tx.commit()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use anyhow::Result;
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    content::traits::Content,
+    register::{ContentRegister, Id},
+    service::{
+        db::{DBFormat, DBHandler},
+        migrations,
+        sqlite::SqliteStorage,
+    },
+};
+
+pub trait Storage {
+    /// None if there is no db yet
+    fn load(&mut self) -> Result<Option<DBHandler>>;
+    fn save(&mut self, register: &ContentRegister<Content, Id>) -> Result<()>;
+    /// writes the item with this id (and the register generation) to the storage
+    fn upsert(&mut self, register: &ContentRegister<Content, Id>, id: Id) -> Result<()>;
+    fn delete(&mut self, register: &ContentRegister<Content, Id>, id: Id) -> Result<()>;
+
+    /// writes only the items with these ids, deleting the ones that are not in the register anymore
+    fn save_changes(&mut self, register: &ContentRegister<Content, Id>, ids: &[Id]) -> Result<()> {
+        for &id in ids {
+            match register.get(id) {
+                Some(_) => self.upsert(register, id)?,
+                None => self.delete(register, id)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// sqlite for *.sqlite, *.sqlite3 and *.db, a yaml/json file otherwise
+pub fn open(path: &Path) -> Result<Box<dyn Storage>> {
+    let storage: Box<dyn Storage> = match path.extension().and_then(|e| e.to_str()) {
+        Some("sqlite") | Some("sqlite3") | Some("db") => Box::new(SqliteStorage::open(path)?),
+        _ => Box::new(FileStorage::new(path)),
+    };
+    Ok(storage)
+}
+
+/// like open, but nothing is ever written: sqlite tables are not created and migrated files are not backed up
+pub fn open_read_only(path: &Path) -> Result<Box<dyn Storage>> {
+    let storage: Box<dyn Storage> = match path.extension().and_then(|e| e.to_str()) {
+        Some("sqlite") | Some("sqlite3") | Some("db") => {
+            Box::new(SqliteStorage::open_read_only(path)?)
+        }
+        _ => Box::new(FileStorage {
+            path: path.into(),
+            read_only: true,
+        }),
+    };
+    Ok(storage)
+}
+
+/// the whole db in a single yaml/json file. there is no partial write, so upsert and delete rewrite the file
+pub struct FileStorage {
+    path: PathBuf,
+    /// loads without backing up a migrated file
+    read_only: bool,
+}
+
+#[derive(Serialize)]
+struct DBRef<'a> {
+    schema_version: u32,
+    register: &'a ContentRegister<Content, Id>,
+}
+
+impl FileStorage {
+    pub fn new<T: Into<PathBuf>>(path: T) -> Self {
+        Self {
+            path: path.into(),
+            read_only: false,
+        }
+    }
+
+    fn write<T: Serialize>(&self, db: &T) -> Result<()> {
+        let mut w = BufWriter::new(File::create(&self.path)?);
+        let s = DBFormat::from_path(&self.path).to_string(db)?;
+        write!(w, "{s}")?;
+        Ok(())
+    }
+
+    fn write_register(&self, register: &ContentRegister<Content, Id>) -> Result<()> {
+        self.write(&DBRef {
+            schema_version: migrations::SCHEMA_VERSION,
+            register,
+        })
+    }
+}
+
+impl Storage for FileStorage {
+    fn load(&mut self) -> Result<Option<DBHandler>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(_) => return Ok(None), // no problem is file does not exist
+        };
+        let mut red = BufReader::new(file);
+        let mut buf = String::new();
+        red.read_to_string(&mut buf)?;
+        let value = DBFormat::from_path(&self.path).parse(&buf)?;
+        let (value, from) = migrations::migrate(value)?;
+        if from != migrations::SCHEMA_VERSION && !self.read_only {
+            // keep the old db around, incase something goes wrong
+            let backup = self.path.with_extension(format!("v{from}.bak"));
+            std::fs::write(&backup, &buf)?;
+            debug!("migrated db from v{from}, old db at {backup:?}");
+        }
+        let dbh = serde_yaml::from_value(value)?;
+        Ok(Some(dbh))
+    }
+
+    fn save(&mut self, register: &ContentRegister<Content, Id>) -> Result<()> {
+        self.write_register(register)
+    }
+
+    fn upsert(&mut self, register: &ContentRegister<Content, Id>, _id: Id) -> Result<()> {
+        self.write_register(register)
+    }
+
+    fn delete(&mut self, register: &ContentRegister<Content, Id>, _id: Id) -> Result<()> {
+        self.write_register(register)
+    }
+
+    fn save_changes(&mut self, register: &ContentRegister<Content, Id>, ids: &[Id]) -> Result<()> {
+        match ids.is_empty() {
+            true => Ok(()),
+            false => self.write_register(register),
+        }
+    }
+}
@@ -0,0 +1,316 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+use tui::style::{Color, Modifier, Style};
+
+use super::config::{config, profile};
+
+/// the theme of the profile in use, dark if it has none
+pub fn theme() -> &'static Theme {
+    let config = config();
+    profile()
+        .theme
+        .as_ref()
+        .and_then(|name| config.themes.get(name))
+        .unwrap_or(&config.themes["dark"])
+}
+
+/// named styles everything is drawn with
+#[derive(Debug, Clone)]
+pub struct Theme {
+    /// items that are not todos, eg lists and overlay entries
+    pub text: Style,
+    pub pending: Style,
+    pub done: Style,
+    pub ignored: Style,
+    pub overdue: Style,
+    /// due dates of today
+    pub today: Style,
+    /// due dates in the next few days
+    pub soon: Style,
+    pub border: Style,
+    pub title: Style,
+    /// patched over the style of the selected item
+    pub selected: Style,
+    /// the cursor while typing
+    pub cursor: Style,
+    /// secondary text, eg tracked time and counts
+    pub dim: Style,
+    /// search matches
+    pub matched: Style,
+    /// items marked with space or V
+    pub marked: Style,
+    pub tag: Style,
+    pub error: Style,
+    /// running timers and pomodoro work
+    pub running: Style,
+    /// the mode badges of the status bar
+    pub normal_mode: Style,
+    pub insert_mode: Style,
+    pub filter_mode: Style,
+    pub visual_mode: Style,
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        let fg = |r, g, b| Style::default().fg(Color::Rgb(r, g, b));
+        let badge = |r, g, b| {
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Rgb(r, g, b))
+                .add_modifier(Modifier::BOLD)
+        };
+        Self {
+            text: fg(200, 200, 100),
+            pending: fg(200, 200, 100),
+            done: fg(90, 130, 90).add_modifier(Modifier::CROSSED_OUT),
+            ignored: Style::default().fg(Color::DarkGray),
+            overdue: fg(220, 90, 90).add_modifier(Modifier::BOLD),
+            today: fg(230, 150, 60).add_modifier(Modifier::BOLD),
+            soon: fg(230, 190, 90),
+            border: fg(150, 150, 150),
+            title: Style::default(),
+            selected: Style::default().add_modifier(Modifier::BOLD),
+            cursor: Style::default().add_modifier(Modifier::REVERSED),
+            dim: Style::default().fg(Color::DarkGray),
+            matched: fg(0, 0, 0).bg(Color::Rgb(230, 150, 60)),
+            marked: fg(170, 120, 200),
+            tag: fg(110, 150, 200),
+            error: fg(220, 90, 90),
+            running: fg(200, 90, 90),
+            normal_mode: badge(110, 150, 200),
+            insert_mode: badge(90, 160, 90),
+            filter_mode: badge(200, 150, 60),
+            visual_mode: badge(170, 120, 200),
+        }
+    }
+
+    pub fn light() -> Self {
+        let fg = |r, g, b| Style::default().fg(Color::Rgb(r, g, b));
+        let badge = |r, g, b| {
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Rgb(r, g, b))
+                .add_modifier(Modifier::BOLD)
+        };
+        Self {
+            text: fg(60, 60, 60),
+            pending: fg(140, 100, 0),
+            done: fg(40, 120, 40).add_modifier(Modifier::CROSSED_OUT),
+            ignored: fg(150, 150, 150),
+            overdue: fg(190, 30, 30).add_modifier(Modifier::BOLD),
+            today: fg(200, 110, 20).add_modifier(Modifier::BOLD),
+            soon: fg(170, 120, 0),
+            border: fg(120, 120, 120),
+            title: Style::default(),
+            selected: Style::default().add_modifier(Modifier::BOLD),
+            cursor: Style::default().add_modifier(Modifier::REVERSED),
+            dim: fg(130, 130, 130),
+            matched: fg(255, 255, 255).bg(Color::Rgb(200, 110, 20)),
+            marked: fg(130, 60, 170),
+            tag: fg(40, 90, 170),
+            error: fg(190, 30, 30),
+            running: fg(190, 30, 30),
+            normal_mode: badge(110, 150, 200),
+            insert_mode: badge(90, 160, 90),
+            filter_mode: badge(200, 150, 60),
+            visual_mode: badge(170, 120, 200),
+        }
+    }
+
+    fn styles_mut(&mut self) -> [(&'static str, &mut Style); 21] {
+        [
+            ("text", &mut self.text),
+            ("pending", &mut self.pending),
+            ("done", &mut self.done),
+            ("ignored", &mut self.ignored),
+            ("overdue", &mut self.overdue),
+            ("today", &mut self.today),
+            ("soon", &mut self.soon),
+            ("border", &mut self.border),
+            ("title", &mut self.title),
+            ("selected", &mut self.selected),
+            ("cursor", &mut self.cursor),
+            ("dim", &mut self.dim),
+            ("matched", &mut self.matched),
+            ("marked", &mut self.marked),
+            ("tag", &mut self.tag),
+            ("error", &mut self.error),
+            ("running", &mut self.running),
+            ("normal_mode", &mut self.normal_mode),
+            ("insert_mode", &mut self.insert_mode),
+            ("filter_mode", &mut self.filter_mode),
+            ("visual_mode", &mut self.visual_mode),
+        ]
+    }
+
+    /// the base theme with the styles of a [themes.<name>] table of the config over it
+    pub fn build(
+        name: &str,
+        table: &BTreeMap<String, String>,
+        themes: &BTreeMap<String, Theme>,
+    ) -> Self {
+        let base = table.get("base").map_or("dark", String::as_str);
+        let mut theme = match themes.get(base) {
+            Some(theme) => theme.clone(),
+            None => {
+                error!("theme {name}: no theme named {base} to base it on");
+                Self::dark()
+            }
+        };
+        for (key, text) in table.iter().filter(|(key, _)| *key != "base") {
+            let style = match theme.styles_mut().into_iter().find(|(n, _)| n == key) {
+                Some((_, style)) => style,
+                None => {
+                    error!("theme {name}: unknown style {key}");
+                    continue;
+                }
+            };
+            match parse_style(text) {
+                Ok(s) => *style = s,
+                Err(err) => error!("theme {name}: {key}: {err}"),
+            }
+        }
+        theme
+    }
+
+    pub fn fit(mut self, colors: ColorSupport) -> Self {
+        for (_, style) in self.styles_mut() {
+            style.fg = style.fg.map(|c| colors.fit(c));
+            style.bg = style.bg.map(|c| colors.fit(c));
+        }
+        self
+    }
+}
+
+/// eg "#c8c864 bold", "black on yellow" or "crossed_out 108". empty for no style
+pub fn parse_style(text: &str) -> Result<Style> {
+    let mut style = Style::default();
+    let mut words = text.split_whitespace();
+    while let Some(word) = words.next() {
+        let word = word.to_lowercase();
+        let modifier = match word.as_str() {
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underlined" => Modifier::UNDERLINED,
+            "reversed" => Modifier::REVERSED,
+            "crossed_out" => Modifier::CROSSED_OUT,
+            "on" => {
+                match words.next() {
+                    Some(bg) => style = style.bg(parse_color(bg)?),
+                    None => bail!("no color after on"),
+                }
+                continue;
+            }
+            fg => {
+                style = style.fg(parse_color(fg)?);
+                continue;
+            }
+        };
+        style = style.add_modifier(modifier);
+    }
+    Ok(style)
+}
+
+/// #rrggbb, 0-255 or one of the 16 names
+fn parse_color(text: &str) -> Result<Color> {
+    let text = text.to_lowercase();
+    if let Some(hex) = text.strip_prefix('#') {
+        let n = u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() == 6);
+        return match n {
+            Some(n) => Ok(Color::Rgb((n >> 16) as u8, (n >> 8) as u8, n as u8)),
+            None => bail!("not a color: #{hex}, expected #rrggbb"),
+        };
+    }
+    if let Ok(i) = text.parse() {
+        return Ok(Color::Indexed(i));
+    }
+    match ANSI16.iter().find(|(name, _, _)| *name == text) {
+        Some(&(_, color, _)) => Ok(color),
+        None => bail!("not a color: {text}"),
+    }
+}
+
+/// name, color and how xterm shows it
+const ANSI16: [(&str, Color, (u8, u8, u8)); 16] = [
+    ("black", Color::Black, (0, 0, 0)),
+    ("red", Color::Red, (205, 0, 0)),
+    ("green", Color::Green, (0, 205, 0)),
+    ("yellow", Color::Yellow, (205, 205, 0)),
+    ("blue", Color::Blue, (0, 0, 238)),
+    ("magenta", Color::Magenta, (205, 0, 205)),
+    ("cyan", Color::Cyan, (0, 205, 205)),
+    ("gray", Color::Gray, (229, 229, 229)),
+    ("darkgray", Color::DarkGray, (127, 127, 127)),
+    ("lightred", Color::LightRed, (255, 0, 0)),
+    ("lightgreen", Color::LightGreen, (0, 255, 0)),
+    ("lightyellow", Color::LightYellow, (255, 255, 0)),
+    ("lightblue", Color::LightBlue, (92, 92, 255)),
+    ("lightmagenta", Color::LightMagenta, (255, 0, 255)),
+    ("lightcyan", Color::LightCyan, (0, 255, 255)),
+    ("white", Color::White, (255, 255, 255)),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSupport {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl Default for ColorSupport {
+    fn default() -> Self {
+        Self::detect()
+    }
+}
+
+impl ColorSupport {
+    /// from $COLORTERM and $TERM
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// truecolor, 256 or 16
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "truecolor" | "24bit" => Some(Self::TrueColor),
+            "256" => Some(Self::Ansi256),
+            "16" => Some(Self::Ansi16),
+            _ => None,
+        }
+    }
+
+    pub fn fit(&self, color: Color) -> Color {
+        match (self, color) {
+            (Self::TrueColor, _) => color,
+            (Self::Ansi256, Color::Rgb(r, g, b)) => {
+                Color::Indexed(ansi_colours::ansi256_from_rgb((r, g, b)))
+            }
+            (Self::Ansi16, Color::Rgb(r, g, b)) => nearest16((r, g, b)),
+            (Self::Ansi16, Color::Indexed(i)) if i < 16 => ANSI16[i as usize].1,
+            (Self::Ansi16, Color::Indexed(i)) => nearest16(ansi_colours::rgb_from_ansi256(i)),
+            _ => color,
+        }
+    }
+}
+
+fn nearest16((r, g, b): (u8, u8, u8)) -> Color {
+    let distance = |&(_, _, (r2, g2, b2)): &(&str, Color, (u8, u8, u8))| {
+        [(r, r2), (g, g2), (b, b2)]
+            .iter()
+            .map(|&(a, b)| (a as i32 - b as i32).pow(2))
+            .sum::<i32>()
+    };
+    ANSI16.iter().min_by_key(|c| distance(c)).unwrap().1
+}
@@ -0,0 +1,123 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use std::collections::HashSet;
+
+use crate::{
+    content::{todo::Todo, todo_list::TodoList, traits::Content},
+    register::{ContentRegister, Id},
+    service::db,
+};
+
+/// an item reachable from the main provider and the way to it
+#[derive(Debug, Clone)]
+pub struct Found {
+    /// the stack that shows the item, main provider first
+    pub path: Vec<Id>,
+    /// what to select in each widget of the path, the item itself last
+    pub selected: Vec<Id>,
+    pub text: String,
+    /// titles of the lists on the way
+    pub context: String,
+}
+
+impl Found {
+    pub fn id(&self) -> Id {
+        *self.selected.last().unwrap()
+    }
+}
+
+/// everything that can be reached from the main provider, in the order it is shown
+pub fn reachable(register: &ContentRegister<Content, Id>, main_provider: Id) -> Vec<Found> {
+    let mut out = vec![];
+    let mut seen = HashSet::from([main_provider.raw()]);
+    walk(
+        register,
+        &mut vec![main_provider],
+        &mut vec![],
+        &mut seen,
+        &mut out,
+    );
+    out
+}
+
+fn walk(
+    register: &ContentRegister<Content, Id>,
+    path: &mut Vec<Id>,
+    selected: &mut Vec<Id>,
+    seen: &mut HashSet<(u64, u64)>,
+    out: &mut Vec<Found>,
+) {
+    let provider = match register
+        .get(*path.last().unwrap())
+        .and_then(|c| c.as_provider())
+    {
+        Some(p) => p,
+        None => return,
+    };
+    let context = path[1..]
+        .iter()
+        .filter_map(|&id| register.get(id))
+        .map(|c| c.as_display().text())
+        .collect::<Vec<_>>()
+        .join(" › ");
+    for child in (0..).map_while(|i| provider.get(i)).collect::<Vec<_>>() {
+        let c = match register.get(child) {
+            Some(c) => c,
+            None => continue,
+        };
+        selected.push(child);
+        out.push(Found {
+            path: path.clone(),
+            selected: selected.clone(),
+            text: c.as_display().text().into_owned(),
+            context: context.clone(),
+        });
+        // the same way MoveRight goes: todos open their subtasks, lists open themselves
+        let next = match c.as_any().downcast_ref::<Todo>() {
+            Some(t) => t.subtasks,
+            None => Some(child).filter(|_| c.as_widget().is_some()),
+        };
+        if let Some(next) = next.filter(|next| seen.insert(next.raw())) {
+            path.push(next);
+            walk(register, path, selected, seen, out);
+            path.pop();
+        }
+        selected.pop();
+    }
+}
+
+/// the ids of everything that can be reached from the main provider of the register, the main provider and
+/// the subtask lists on the way included. whatever is not in here is never shown
+pub fn reachable_ids(register: &ContentRegister<Content, Id>) -> HashSet<Id> {
+    let main_provider = match db::main_provider(register) {
+        Some(id) => id,
+        None => return HashSet::new(),
+    };
+    reachable(register, main_provider)
+        .into_iter()
+        .flat_map(|f| {
+            let id = f.id();
+            f.path.into_iter().chain([id])
+        })
+        .collect()
+}
+
+/// the lists shown from the main provider with this title, ignoring case. subtask lists don't count
+pub fn lists_called(
+    register: &ContentRegister<Content, Id>,
+    main_provider: Id,
+    title: &str,
+) -> Vec<(Id, String)> {
+    reachable(register, main_provider)
+        .into_iter()
+        .map(|f| f.id())
+        .filter_map(|id| {
+            register
+                .get(id)
+                .filter(|c| c.as_any().is::<TodoList>())
+                .map(|c| (id, c.as_display().text().into_owned()))
+        })
+        .filter(|(_, t)| t.eq_ignore_ascii_case(title))
+        .collect()
+}
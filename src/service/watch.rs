@@ -0,0 +1,44 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::Path,
+    time::SystemTime,
+};
+
+/// what the db file looked like the last time we read or wrote it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStamp {
+    mtime: Option<SystemTime>,
+    hash: u64,
+}
+
+impl FileStamp {
+    /// None if the file does not exist
+    pub fn read(path: &Path) -> Option<Self> {
+        let mtime = std::fs::metadata(path).ok()?.modified().ok();
+        let bytes = std::fs::read(path).ok()?;
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        Some(Self {
+            mtime,
+            hash: hasher.finish(),
+        })
+    }
+
+    /// only hashes the file again if the mtime changed, so this is cheap enough to poll
+    pub fn changed(stamp: Option<&Self>, path: &Path) -> Option<Self> {
+        let mtime = std::fs::metadata(path).ok().and_then(|m| m.modified().ok());
+        if stamp.map(|s| s.mtime) == Some(mtime) || (stamp.is_none() && mtime.is_none()) {
+            return None;
+        }
+        let new = Self::read(path);
+        if new.as_ref() == stamp {
+            None
+        } else {
+            new
+        }
+    }
+}
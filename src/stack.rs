@@ -46,6 +46,17 @@ impl ContentStack {
         self.stack.len()
     }
 
+    /// drops everything from the first id that is no longer valid, e.g. after the db was reloaded.
+    /// false if even the main provider is not valid, the stack is left as it is then
+    pub fn truncate_invalid<F: Fn(Id) -> bool>(&mut self, valid: F) -> bool {
+        let len = self.stack.iter().take_while(|&&id| valid(id)).count();
+        if len == 0 {
+            return false;
+        }
+        self.stack.truncate(len);
+        true
+    }
+
     pub fn get(&self, index: usize) -> Id {
         self.stack[index]
     }
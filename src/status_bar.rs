@@ -0,0 +1,85 @@
+#[allow(unused_imports)]
+use crate::{dbg, debug, error};
+
+use std::{
+    borrow::Cow,
+    time::{Duration, Instant},
+};
+use tui::text::{Span, Spans};
+
+use crate::{service::theme::theme, traits::Mode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Error,
+}
+
+impl Level {
+    /// how long a message stays up
+    fn timeout(&self) -> Duration {
+        match self {
+            Self::Info => Duration::from_secs(4),
+            Self::Error => Duration::from_secs(8),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub text: Cow<'static, str>,
+    pub level: Level,
+    posted: Instant,
+}
+
+/// the line at the bottom. messages are posted with AppAction::Message, the rest is filled in on every render
+#[derive(Debug, Default)]
+pub struct StatusBar {
+    /// newest last
+    messages: Vec<Message>,
+}
+
+impl StatusBar {
+    pub fn post<T: Into<Cow<'static, str>>>(&mut self, text: T, level: Level) {
+        let text = text.into();
+        debug!("status: {:?} {}", level, text);
+        self.messages.push(Message {
+            text,
+            level,
+            posted: Instant::now(),
+        });
+    }
+
+    pub fn info<T: Into<Cow<'static, str>>>(&mut self, text: T) {
+        self.post(text, Level::Info)
+    }
+
+    pub fn error<T: Into<Cow<'static, str>>>(&mut self, text: T) {
+        self.post(text, Level::Error)
+    }
+
+    /// the newest message that has not timed out yet
+    pub fn message(&mut self) -> Option<&Message> {
+        self.messages
+            .retain(|m| m.posted.elapsed() < m.level.timeout());
+        self.messages.last()
+    }
+
+    pub fn mode_span(mode: Mode) -> Span<'static> {
+        let (name, style) = match mode {
+            Mode::Normal => ("NORMAL", theme().normal_mode),
+            Mode::Insert => ("INSERT", theme().insert_mode),
+            Mode::Filter => ("FILTER", theme().filter_mode),
+            Mode::Visual => ("VISUAL", theme().visual_mode),
+        };
+        Span::styled(format!(" {name} "), style)
+    }
+
+    pub fn message_spans(message: &Message) -> Spans<'static> {
+        let style = match message.level {
+            Level::Info => theme().text,
+            Level::Error => theme().error,
+        };
+        Spans::from(Span::styled(message.text.clone(), style))
+    }
+}
@@ -2,11 +2,11 @@
 use crate::{dbg, debug, error};
 
 use anyhow::Result;
-use crossterm::event::Event;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 use std::borrow::Cow;
-use tui::{backend::Backend, widgets::ListState, Frame};
+use tui::{backend::Backend, layout::Rect, widgets::ListState, Frame};
 
-use crate::{app::AppAction, content::traits::Content, register::Id};
+use crate::{app::AppAction, content::traits::Content, register::Id, service::config::config};
 
 pub enum EventAction<T> {
     Absorbed(T),
@@ -45,6 +45,45 @@ pub trait YankDest {
     fn remove(&mut self, q: Self::Query) -> bool;
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    Insert,
+    /// typing a search
+    Filter,
+    /// selecting a range with V
+    Visual,
+}
+
+/// what the status bar shows about the widget in view
+pub trait Status {
+    fn mode(&self) -> Mode;
+    /// keys of a sequence that is not complete yet
+    fn pending_keys(&self) -> String;
+    /// the items the pending/done counts are taken over
+    fn counted(&self) -> &[Id];
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CommandInfo {
+    pub name: &'static str,
+    /// how the arguments look, empty if there are none
+    pub args: &'static str,
+    pub description: &'static str,
+}
+
+/// commands listed in the palette and run as `:name args`
+pub trait Commands {
+    fn commands(&self) -> Vec<CommandInfo>;
+    /// None if there is no such command
+    fn run_command(&mut self, name: &str, args: &str, self_id: Id) -> Option<AppAction>;
+}
+
+/// the event of pressing a key, for commands that do what a key does
+pub fn key_press(code: KeyCode, modifiers: KeyModifiers) -> Event {
+    Event::Key(KeyEvent::new(code, modifiers))
+}
+
 pub trait Provider<'a> {
     // ? matbe GAT
     type Item;
@@ -54,40 +93,162 @@ pub trait Provider<'a> {
     fn get_selected(&self) -> Self::Item;
 }
 
+/// where zz, zt and zb put the selected item
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Top,
+    Center,
+    Bottom,
+}
+
 /// wrapping ListState to make sure not to call select(None) and to eliminate the use of unwrap() on selected_index()
-/// currently, theres no way to access/suggest the offset
+/// the offset is kept here rather than in the ListState, which has no way to access it
 #[derive(Debug, Clone)]
 pub struct SelectedIndex {
     index: ListState,
+    /// the first item drawn
+    offset: usize,
+    /// where the selected item goes in the next draw, the heights are only known then
+    align: Option<Align>,
+    /// rows inside the borders in the last draw
+    height: usize,
+    /// (area, area inside the borders) of each list drawn with this index in the last draw
+    areas: Vec<(Rect, Rect)>,
+    /// rows taken by each item drawn, starting at offset
+    rows: Vec<usize>,
 }
 impl Default for SelectedIndex {
     fn default() -> Self {
         Self::new()
     }
 }
-impl Into<ListState> for SelectedIndex {
-    fn into(self) -> ListState {
-        self.index
-    }
-}
-impl<'a> Into<&'a mut ListState> for &'a mut SelectedIndex {
-    fn into(self) -> &'a mut ListState {
-        &mut self.index
-    }
-}
 impl SelectedIndex {
     pub fn new() -> Self {
         let mut state = ListState::default();
         state.select(Some(0));
-        Self { index: state }
+        Self {
+            index: state,
+            ..Self::none()
+        }
     }
 
     pub fn none() -> Self {
         Self {
             index: Default::default(),
+            offset: 0,
+            align: None,
+            height: 0,
+            areas: vec![],
+            rows: vec![],
         }
     }
 
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// the state to render the items from offset on with
+    pub fn state(&self) -> ListState {
+        let mut state = ListState::default();
+        state.select(self.index.selected().map(|i| i.saturating_sub(self.offset)));
+        state
+    }
+
+    /// moves the offset just enough for the selected item and scrolloff items around it to fit in the
+    /// height, or to where align asked for
+    pub fn scroll(&mut self, rows: &[usize], height: usize) {
+        self.height = height;
+        if rows.is_empty() {
+            self.offset = 0;
+            return;
+        }
+        // the items might have gone since the last draw
+        self.offset = self.offset.min(rows.len() - 1);
+        let selected = match self.index.selected() {
+            Some(i) => i.min(rows.len() - 1),
+            None => return,
+        };
+        let margin = config().scrolloff.min(height.saturating_sub(1) / 2);
+        let above = selected.saturating_sub(margin);
+        let below = (selected + margin).min(rows.len() - 1);
+        let fits =
+            |from: usize, to: usize, room: usize| rows[from..=to].iter().sum::<usize>() <= room;
+        match self.align.take() {
+            Some(Align::Top) => self.offset = above,
+            Some(Align::Center) => {
+                let room = height.saturating_sub(rows[selected]) / 2;
+                self.offset = selected;
+                while self.offset > 0 && fits(self.offset - 1, selected - 1, room) {
+                    self.offset -= 1;
+                }
+            }
+            Some(Align::Bottom) => {
+                self.offset = selected;
+                while self.offset > 0 && fits(self.offset - 1, below, height) {
+                    self.offset -= 1;
+                }
+            }
+            None => self.offset = self.offset.min(above),
+        }
+        while self.offset < selected && !fits(self.offset, below, height) {
+            self.offset += 1;
+        }
+    }
+
+    /// puts the selected item at the top, center or bottom in the next draw
+    pub fn align(&mut self, align: Align) {
+        self.align = Some(align);
+    }
+
+    /// how many items the last draw had room for, at least 1
+    pub fn visible(&self) -> usize {
+        let mut used = 0;
+        self.rows
+            .iter()
+            .take_while(|&&rows| {
+                used += rows;
+                used <= self.height
+            })
+            .count()
+            .max(1)
+    }
+
+    /// moves the view and the selection by n items, staying among the len items
+    pub fn page(&mut self, n: isize, len: usize) {
+        let last = len.saturating_sub(1);
+        let by = |i: usize| i.saturating_add_signed(n).min(last);
+        self.offset = by(self.offset);
+        if let Some(i) = self.index.selected() {
+            self.index.select(Some(by(i)));
+        }
+    }
+
+    /// remembers where the items went, for finding what the mouse is on
+    pub fn set_drawn(&mut self, areas: Vec<(Rect, Rect)>, rows: Vec<usize>) {
+        self.areas = areas;
+        self.rows = rows;
+    }
+
+    /// the item drawn at the cell in the last draw
+    pub fn item_at(&self, column: u16, row: u16) -> Option<usize> {
+        let (_, inner) = self.areas.iter().find(|(_, i)| contains(*i, column, row))?;
+        let mut top = inner.y as usize;
+        for (i, &rows) in self.rows.iter().enumerate() {
+            top += rows;
+            if (row as usize) < top {
+                return Some(self.offset + i);
+            }
+        }
+        None
+    }
+
+    /// whether the cell is on the top border of a list, where its title is
+    pub fn title_at(&self, column: u16, row: u16) -> bool {
+        self.areas
+            .iter()
+            .any(|(a, i)| row == a.y && i.y > a.y && contains(*a, column, row))
+    }
+
     pub fn selected_index(&self) -> usize {
         self.index.selected().unwrap()
     }
@@ -95,4 +256,43 @@ impl SelectedIndex {
     pub fn select(&mut self, index: usize) {
         self.index.select(Some(index));
     }
+
+    /// selects the first item and scrolls back up to it
+    pub fn reset(&mut self) {
+        self.select(0);
+        self.offset = 0;
+    }
+}
+
+fn contains(area: Rect, column: u16, row: u16) -> bool {
+    (area.x..area.x + area.width).contains(&column) && (area.y..area.y + area.height).contains(&row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scroll_keeps_the_offset_among_the_items() {
+        let mut index = SelectedIndex::new();
+        index.select(29);
+        index.scroll(&[1; 30], 5);
+        assert!(index.offset() > 0);
+        index.scroll(&[], 5);
+        assert_eq!(index.offset(), 0);
+
+        index.scroll(&[1; 30], 5);
+        index.select(0);
+        index.scroll(&[1; 3], 5);
+        assert_eq!(index.offset(), 0);
+    }
+
+    #[test]
+    fn reset_scrolls_back_to_the_top() {
+        let mut index = SelectedIndex::new();
+        index.select(29);
+        index.scroll(&[1; 30], 5);
+        index.reset();
+        assert_eq!((index.selected_index(), index.offset()), (0, 0));
+    }
 }